@@ -0,0 +1,117 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::navigation::{reachable_points, ReachablePoint};
+use crate::policy::{Outcome, Policy};
+use crate::simulate::{fight, hp};
+
+pub const MAX_STEPS: usize = 256;
+// nodes asking for a choice get the first option, the rest are moved to without one
+const SELECTION_ATTEMPTS: [&[u8]; 2] = [&[], &[0]];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    // a battle was lost or the warrior ran out of hp
+    Died,
+    // the map offered nowhere left to go
+    Completed,
+    // only already visited nodes were reachable
+    Stuck,
+    // a battle was still going after the turn limit and is left pending
+    Unfinished,
+    StepLimit,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub outcome: RunOutcome,
+    pub path: Vec<(u8, u8)>,
+    pub battles_won: u32,
+    pub battles_lost: u32,
+    pub battle_turns: usize,
+    pub warrior: Option<Value>,
+}
+
+fn move_to(context: &Context, x: u8, y: u8) -> Result<Value, GameError> {
+    let [attempts @ .., last] = SELECTION_ATTEMPTS;
+    for selections in attempts {
+        match context.move_player(x, y, selections.to_vec(), &JsonRenderer) {
+            Err(GameError::CoreError { .. }) => continue,
+            result => return result,
+        }
+    }
+    context.move_player(x, y, last.to_vec(), &JsonRenderer)
+}
+
+// walks the session of `context` node by node, fighting every battle with `policy`
+pub fn autopilot(
+    context: &Context,
+    policy: Policy,
+    max_steps: usize,
+) -> Result<RunSummary, GameError> {
+    // refused before the first move, not halfway through the run at its first battle
+    policy.ensure_allowed(context)?;
+    let mut visited = BTreeSet::new();
+    let mut summary = RunSummary {
+        outcome: RunOutcome::StepLimit,
+        path: vec![],
+        battles_won: 0,
+        battles_lost: 0,
+        battle_turns: 0,
+        warrior: None,
+    };
+    for _ in 0..max_steps {
+        let points = reachable_points(context)?;
+        if points.is_empty() {
+            summary.outcome = RunOutcome::Completed;
+            break;
+        }
+        let unvisited = points.iter().find(|v| !visited.contains(&v.point));
+        let Some(&ReachablePoint { point: (x, y), .. }) = unvisited else {
+            summary.outcome = RunOutcome::Stuck;
+            break;
+        };
+        move_to(context, x, y)?;
+        visited.insert((x, y));
+        summary.path.push((x, y));
+        if context.has_battle()? {
+            let result = fight(context, policy, |_| {})?;
+            summary.battle_turns += result.turns;
+            match result.outcome {
+                Outcome::Win => summary.battles_won += 1,
+                Outcome::Lose => {
+                    summary.battles_lost += 1;
+                    summary.outcome = RunOutcome::Died;
+                    break;
+                }
+                Outcome::Ongoing => {
+                    summary.outcome = RunOutcome::Unfinished;
+                    break;
+                }
+            }
+        }
+        if hp(context)? == 0 {
+            summary.outcome = RunOutcome::Died;
+            break;
+        }
+    }
+    summary.warrior = context.warrior_profile(&JsonRenderer).ok();
+    Ok(summary)
+}
+
+// plays a whole run on a throwaway game, to check a seed and resource pool before publishing
+pub fn smoke_test_run(
+    raw_resource_pool: &[u8],
+    seed: u64,
+    player_id: u16,
+    point: (u8, u8),
+    policy: Policy,
+) -> Result<RunSummary, GameError> {
+    let context = Context::new(raw_resource_pool, seed)?;
+    context.create_session(player_id, point.0, point.1, &[])?;
+    autopilot(&context, policy, MAX_STEPS)
+}
@@ -0,0 +1,363 @@
+use std::cell::Cell;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use spore_warriors_wasm::autopilot::MAX_STEPS;
+use spore_warriors_wasm::events::BattleEvent;
+use spore_warriors_wasm::navigation::{map_layout, reachable_points, MapLayout, ReachablePoint};
+use spore_warriors_wasm::policy::outcome;
+use spore_warriors_wasm::{autopilot, list_warriors, Context, JsonRenderer, Policy};
+
+const USAGE: &str = "usage: spore-warriors-cli --resources <file> --seed <u64> --player <u16> \
+[--point <x,y>] [--potion <file>] [--script <file>] [--derive-seeds]";
+
+const HELP: &str = "\
+commands:
+  map [json]             draw the map, or print its profile
+  warrior                print the warrior profile
+  deck                   print the warrior deck profile
+  potion                 print the potion, if any
+  peek <x> <y>           show the node reachable at x,y
+  around                 list every point reachable from here
+  move <x> <y> [sel..]   move to x,y, choosing the given selections
+  battle                 start the battle triggered by the last move, printing its logs
+  play <json>            run a battle iteration, e.g. play [\"EndTurn\"]
+  hint [policy]          suggest the next battle inputs, policy defaults to Greedy
+  auto [policy]          let a policy play the rest of the turn
+  autopilot [policy]     play the rest of the run automatically and print a summary
+  target <json>          check whether a selection is a valid target
+  leave                  destroy the finished battle and return to the map
+  undo                   take back the last move or battle step
+  redo                   replay the last undone step
+  snapshot <file>        write a snapshot of the run
+  load <file>            restore a snapshot
+  record <on|off>        toggle replay recording
+  replay <file>          write the recorded replay
+  health                 print subsystem health
+  seeds                  print the master, map and battle seeds
+  warriors               list the warriors of the pool and where they can start
+  help                   print this help
+  quit                   exit";
+
+// playtests get a generous undo history, there is no ranked play from the terminal
+const HISTORY: usize = 64;
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+struct Options {
+    resources: String,
+    seed: u64,
+    player_id: u16,
+    point: (u8, u8),
+    potion: Option<String>,
+    script: Option<String>,
+    // derive the game seed from --seed and --player instead of using --seed as is
+    derive_seeds: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> CliResult<Self> {
+        let (mut resources, mut seed, mut player_id) = (None, None, None);
+        let mut point = (0, 0);
+        let (mut potion, mut script) = (None, None);
+        let mut derive_seeds = false;
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {flag}"));
+            match flag.as_str() {
+                "--resources" => resources = Some(value()?),
+                "--seed" => seed = Some(value()?.parse()?),
+                "--player" => player_id = Some(value()?.parse()?),
+                "--point" => {
+                    let value = value()?;
+                    let (x, y) = value.split_once(',').ok_or("point must be <x,y>")?;
+                    point = (x.trim().parse()?, y.trim().parse()?);
+                }
+                "--potion" => potion = Some(value()?),
+                "--script" => script = Some(value()?),
+                "--derive-seeds" => derive_seeds = true,
+                _ => return Err(format!("unknown argument {flag}").into()),
+            }
+        }
+        Ok(Self {
+            resources: resources.ok_or("--resources is required")?,
+            seed: seed.ok_or("--seed is required")?,
+            player_id: player_id.ok_or("--player is required")?,
+            point,
+            potion,
+            script,
+            derive_seeds,
+        })
+    }
+}
+
+fn print_json(value: &serde_json::Value) -> CliResult<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+// a node is drawn by the first letter of its kind, the first text field or variant name
+fn glyph(node: &serde_json::Value) -> char {
+    let kind = match node {
+        serde_json::Value::String(kind) => Some(kind.as_str()),
+        serde_json::Value::Object(fields) => fields
+            .values()
+            .find_map(serde_json::Value::as_str)
+            .or_else(|| {
+                fields
+                    .iter()
+                    .find(|(key, value)| key.as_str() != "point" && value.is_object())
+                    .map(|(key, _)| key.as_str())
+            }),
+        _ => None,
+    };
+    kind.and_then(|v| v.chars().next()).unwrap_or('#')
+}
+
+// `@` is the warrior, letters are nodes and `*` marks the ones reachable next
+fn print_map(layout: &MapLayout, reachable: &BTreeSet<(u8, u8)>) {
+    print!("   ");
+    for x in 0..layout.columns {
+        print!("{:<2}", x % 100);
+    }
+    println!();
+    for y in 0..layout.rows {
+        print!("{:>2} ", y);
+        for x in 0..layout.columns {
+            let point = (x as u8, y as u8);
+            let cell = match layout.nodes.get(&point) {
+                _ if layout.position == Some(point) => '@',
+                Some(node) => glyph(node),
+                None => '.',
+            };
+            let mark = if reachable.contains(&point) { '*' } else { ' ' };
+            print!("{cell}{mark}");
+        }
+        println!();
+    }
+}
+
+// one line per battle step: the turn reached, how the fight stands and how much happened
+fn print_step(step: &serde_json::Value, turn: Option<usize>) {
+    let logs = step[1].as_array().map_or(0, Vec::len);
+    let turn = turn.map_or_else(|| "-".to_string(), |v| v.to_string());
+    match outcome(&step[0]) {
+        Ok(outcome) => println!("turn {turn}: {outcome:?}, {logs} logs"),
+        Err(e) => println!("turn {turn}: {e}, {logs} logs"),
+    }
+}
+
+fn print_event(event: BattleEvent) {
+    println!(
+        "turn {} #{} {:?}: {}",
+        event.turn, event.sequence, event.actor, event.log
+    );
+}
+
+fn arg<T: std::str::FromStr>(args: &[&str], index: usize, name: &str) -> CliResult<T>
+where
+    T::Err: Error + 'static,
+{
+    let value = args.get(index).ok_or(format!("missing <{name}>"))?;
+    Ok(value.parse()?)
+}
+
+fn policy(args: &[&str]) -> CliResult<Policy> {
+    match args.get(1) {
+        Some(policy) => Ok(serde_json::from_value(serde_json::json!(policy))?),
+        None => Ok(Policy::default()),
+    }
+}
+
+// everything after the command word, so json payloads may contain spaces
+fn rest<'a>(line: &'a str, command: &str) -> CliResult<&'a str> {
+    let rest = line.trim_start()[command.len()..].trim();
+    if rest.is_empty() {
+        return Err("missing <json>".into());
+    }
+    Ok(rest)
+}
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+fn execute(context: &Context, line: &str) -> CliResult<Flow> {
+    let args: Vec<&str> = line.split_whitespace().collect();
+    let Some(&command) = args.first() else {
+        return Ok(Flow::Continue);
+    };
+    match command {
+        "map" if args.get(1) == Some(&"json") => print_json(&context.map_profile(&JsonRenderer)?)?,
+        "map" => {
+            let reachable = reachable_points(context)?.into_iter().map(|v| v.point);
+            print_map(&map_layout(context)?, &reachable.collect());
+        }
+        "warrior" => print_json(&context.warrior_profile(&JsonRenderer)?)?,
+        "deck" => print_json(&context.deck_profile(&JsonRenderer)?)?,
+        "potion" => match context.potion(&JsonRenderer)? {
+            Some(potion) => print_json(&potion)?,
+            None => println!("no potion"),
+        },
+        "peek" => {
+            let (x, y) = (arg(&args, 1, "x")?, arg(&args, 2, "y")?);
+            match context.peak_movement(x, y, &JsonRenderer)? {
+                Some(node) => print_json(&node)?,
+                None => println!("({x}, {y}) is not reachable"),
+            }
+        }
+        "around" => {
+            for ReachablePoint { point, node } in reachable_points(context)? {
+                println!("{point:?} {node}");
+            }
+        }
+        "move" => {
+            let (x, y) = (arg(&args, 1, "x")?, arg(&args, 2, "y")?);
+            let selections = (3..args.len())
+                .map(|i| arg(&args, i, "selection"))
+                .collect::<CliResult<_>>()?;
+            let result =
+                context.guarded(|| context.move_player(x, y, selections, &JsonRenderer))?;
+            print_json(&result)?;
+            if context.has_battle()? {
+                println!("a battle was triggered, use `battle` to start it");
+            }
+        }
+        "battle" => {
+            let turn = Cell::new(None);
+            let result = context.guarded(|| {
+                context.start_battle_with(&JsonRenderer, |event| {
+                    turn.set(Some(event.turn));
+                    print_event(event)
+                })
+            })?;
+            print_step(&result, turn.get());
+        }
+        "play" => {
+            let operations = serde_json::from_str(rest(line, command)?)?;
+            let turn = Cell::new(None);
+            let result = context.guarded(|| {
+                context.iterate_battle_with(operations, &JsonRenderer, |event| {
+                    turn.set(Some(event.turn));
+                    print_event(event)
+                })
+            })?;
+            print_step(&result, turn.get());
+        }
+        "hint" => print_json(&policy(&args)?.next_inputs(context, 0)?)?,
+        "auto" => {
+            let policy = policy(&args)?;
+            let turn = Cell::new(None);
+            let step = context.guarded(|| {
+                policy.play_turn(context, |event| {
+                    turn.set(Some(event.turn));
+                    print_event(event)
+                })
+            })?;
+            print_step(&step, turn.get());
+        }
+        "autopilot" => {
+            let policy = policy(&args)?;
+            let summary = context.guarded(|| autopilot(context, policy, MAX_STEPS))?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        "target" => {
+            let selection = serde_json::from_str(rest(line, command)?)?;
+            println!("{}", context.peak_target(selection)?);
+        }
+        "leave" => context.guarded(|| context.destroy_battle())?,
+        "undo" => context.guarded(|| context.undo())?,
+        "redo" => context.guarded(|| context.redo())?,
+        "snapshot" => fs::write(arg::<String>(&args, 1, "file")?, context.export_snapshot()?)?,
+        "load" => context.import_snapshot(&fs::read(arg::<String>(&args, 1, "file")?)?)?,
+        "record" => match args.get(1) {
+            Some(&"on") => context.set_replay_recording(true)?,
+            Some(&"off") => context.set_replay_recording(false)?,
+            _ => return Err("record takes on or off".into()),
+        },
+        "replay" => fs::write(arg::<String>(&args, 1, "file")?, context.export_replay()?)?,
+        "health" => println!("{}", serde_json::to_string_pretty(&context.health())?),
+        "seeds" => println!("{}", serde_json::to_string_pretty(&context.seed_info())?),
+        "warriors" => println!(
+            "{}",
+            serde_json::to_string_pretty(&list_warriors(context)?)?
+        ),
+        "help" => println!("{HELP}"),
+        "quit" | "exit" => return Ok(Flow::Quit),
+        _ => return Err(format!("unknown command {command}, try `help`").into()),
+    }
+    Ok(Flow::Continue)
+}
+
+fn start(options: &Options) -> CliResult<Arc<Context>> {
+    let resource_pool = fs::read(&options.resources)?;
+    let potion = match &options.potion {
+        Some(path) => fs::read(path)?,
+        None => Vec::new(),
+    };
+    let context = if options.derive_seeds {
+        Context::create_seeded(&resource_pool, options.seed, options.player_id)?
+    } else {
+        Context::create(&resource_pool, options.seed)?
+    };
+    let (x, y) = options.point;
+    context.create_session(options.player_id, x, y, &potion)?;
+    context.set_history_capacity(HISTORY)?;
+    Ok(context)
+}
+
+fn run(options: Options) -> CliResult<()> {
+    let context = start(&options)?;
+    if let Some(script) = &options.script {
+        // scripts stop at the first failing command so a broken playtest is obvious
+        for (number, line) in fs::read_to_string(script)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            println!("> {line}");
+            match execute(&context, line) {
+                Ok(Flow::Continue) => {}
+                Ok(Flow::Quit) => break,
+                Err(e) => return Err(format!("{script}:{}: {e}", number + 1).into()),
+            }
+        }
+        return Ok(());
+    }
+    println!("{HELP}");
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        match execute(&context, &line) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => return Ok(()),
+            Err(e) => eprintln!("error: {e}"),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
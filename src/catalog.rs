@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::inspect::warrior_ids;
+use crate::navigation::{starting_points, trial_session};
+use crate::potion::cards;
+
+#[derive(Debug, Serialize)]
+pub struct WarriorEntry {
+    pub player_id: u16,
+    pub name: Value,
+    // numeric fields of the warrior profile before any potion or battle
+    pub stats: BTreeMap<String, Value>,
+    pub deck: Vec<Value>,
+    // starting areas of this game's map that name the warrior
+    pub starting_points: Vec<(u8, u8)>,
+}
+
+// every warrior a session of `context` can be started with. starting points come from the
+// map's scene data, and one throwaway session per warrior lets core confirm them and
+// provide the profile
+pub fn list_warriors(context: &Context) -> Result<Vec<WarriorEntry>, GameError> {
+    let raw_resource_pool = context.resource_pool();
+    let (seed, _) = context.journal_actions()?;
+    let map = Context::new(raw_resource_pool, seed)?.map_profile(&JsonRenderer)?;
+    let mut warriors = warrior_ids(raw_resource_pool)?;
+    // derived seeds only fit the warrior they were derived for
+    if let Some(player_id) = context.seed_info().player_id {
+        warriors.retain(|v| *v == player_id);
+    }
+    let mut entries = vec![];
+    for player_id in warriors {
+        let starting_points = starting_points(&map, player_id);
+        let mut profile = None;
+        for &point in &starting_points {
+            match trial_session(raw_resource_pool, seed, player_id, point, &[]) {
+                Ok(trial) => {
+                    profile = Some((
+                        trial.warrior_profile(&JsonRenderer)?,
+                        trial.deck_profile(&JsonRenderer)?,
+                    ));
+                    break;
+                }
+                Err(GameError::CoreError { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        // a warrior core accepts at none of its starting areas cannot be selected at all
+        let Some((warrior, deck)) = profile else {
+            continue;
+        };
+        let stats = warrior
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| value.is_number())
+            .map(|(stat, value)| (stat.clone(), value.clone()))
+            .collect();
+        entries.push(WarriorEntry {
+            player_id,
+            name: warrior.get("name").cloned().unwrap_or_default(),
+            stats,
+            deck: cards(&deck),
+            starting_points,
+        });
+    }
+    Ok(entries)
+}
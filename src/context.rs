@@ -0,0 +1,976 @@
+use std::collections::BTreeSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use spore_warriors_core::battle::pve::MapBattlePVE;
+use spore_warriors_core::battle::traits::{IterationInput, Selection, SimplePVE};
+use spore_warriors_core::contexts::{WarriorContext, WarriorDeckContext};
+use spore_warriors_core::game::Game;
+use spore_warriors_core::map::MoveResult;
+use spore_warriors_core::wrappings::{Enemy, Point};
+
+use crate::daily::DailyChallenge;
+use crate::error::GameError;
+use crate::events::{self, BattleEvent};
+use crate::hash::blake2b_256;
+use crate::health::{Health, SubsystemHealth};
+use crate::history::History;
+use crate::lock::StateLock;
+use crate::navigation::map_nodes;
+use crate::replay::{Divergence, Replay, ReplayReport, ReplayStep};
+use crate::resources::unpack_resource_binary;
+use crate::seed::SeedInfo;
+use crate::snapshot::{Action, Journal, Snapshot};
+
+// every live context, so `reset_all` can reach games still held by the caller
+static CONTEXTS: Mutex<Vec<Weak<Context>>> = Mutex::new(Vec::new());
+
+// turns core payloads into whatever the caller consumes, js values for the wasm facade
+// or json for native users
+pub trait Renderer {
+    type Output;
+
+    fn render<V: Serialize + ?Sized>(&self, value: &V) -> Result<Self::Output, GameError>;
+}
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    type Output = serde_json::Value;
+
+    fn render<V: Serialize + ?Sized>(&self, value: &V) -> Result<Self::Output, GameError> {
+        serde_json::to_value(value).map_err(GameError::serialize("output"))
+    }
+}
+
+struct Discard;
+
+impl Renderer for Discard {
+    type Output = ();
+
+    fn render<V: Serialize + ?Sized>(&self, _: &V) -> Result<Self::Output, GameError> {
+        Ok(())
+    }
+}
+
+fn to_json<T: Serialize>(value: &T, subject: &'static str) -> Result<serde_json::Value, GameError> {
+    serde_json::to_value(value).map_err(GameError::serialize(subject))
+}
+
+fn from_json<T: DeserializeOwned>(
+    value: serde_json::Value,
+    subject: &'static str,
+) -> Result<T, GameError> {
+    serde_json::from_value(value).map_err(GameError::deserialize(subject))
+}
+
+// everything core reads or changes, behind one lock so no two calls can take its parts in
+// different orders
+#[derive(Default)]
+struct State {
+    game: Option<Game>,
+    warrior: Option<WarriorContext>,
+    deck: Option<WarriorDeckContext>,
+    battle: Option<MapBattlePVE>,
+    // a game built from the pending battle's own seed, only its controller is ever used
+    battle_game: Option<Game>,
+    // battles that became pending so far, the index of each battle's seed
+    battles: u64,
+    // the warrior and deck each destroyed battle handed back, in journal order, so a fork
+    // can skip the fights that are already over
+    settled: Vec<(serde_json::Value, serde_json::Value)>,
+    // every point core previewed a move to since the session began, gathered as the warrior
+    // moves since what it can reach changes with where it stands
+    revealed: BTreeSet<(u8, u8)>,
+    // set by `recover` on the state it replaced, calls that were waiting on it go and find
+    // the new one
+    retired: bool,
+}
+
+// per-instance game state, shared by every handle created from the same game.
+// locks are taken in one order: `state`, then `journal`, then `history`, and the last two
+// are only held long enough to read or append, never while waiting on another lock
+#[derive(Default)]
+pub struct Context {
+    resource_pool: Vec<u8>,
+    // content hash from the resource header, so a rebuild of the same pools keeps
+    // snapshots and replays valid
+    resource_pool_hash: [u8; 32],
+    // swapped for a fresh lock by `recover`, a call that traps on wasm32 never releases the
+    // one it holds
+    state: Mutex<Arc<StateLock<State>>>,
+    journal: StateLock<Journal>,
+    history: StateLock<History>,
+    seeds: SeedInfo,
+    daily: Option<DailyChallenge>,
+    ranked: bool,
+}
+
+impl Context {
+    pub fn create(raw_resource_pool: &[u8], seed: u64) -> Result<Arc<Self>, GameError> {
+        Ok(Self::new(raw_resource_pool, seed)?.register())
+    }
+
+    // ranked games cannot turn on undo history
+    pub fn create_ranked(raw_resource_pool: &[u8], seed: u64) -> Result<Arc<Self>, GameError> {
+        let context = Self {
+            ranked: true,
+            ..Self::new(raw_resource_pool, seed)?
+        };
+        Ok(context.register())
+    }
+
+    pub fn is_ranked(&self) -> bool {
+        self.ranked
+    }
+
+    // the game seed is derived from `master_seed`, and only `player_id` may start a session
+    pub fn create_seeded(
+        raw_resource_pool: &[u8],
+        master_seed: u64,
+        player_id: u16,
+    ) -> Result<Arc<Self>, GameError> {
+        let seeds = SeedInfo::derive(master_seed, player_id);
+        let context = Self {
+            seeds,
+            ..Self::new(raw_resource_pool, seeds.map)?
+        };
+        Ok(context.register())
+    }
+
+    pub fn create_daily(
+        raw_resource_pool: &[u8],
+        challenge: DailyChallenge,
+    ) -> Result<Arc<Self>, GameError> {
+        Ok(Self::new_daily(raw_resource_pool, challenge)?.register())
+    }
+
+    // daily games are ranked and start their one session right away, at the challenge's point
+    // and without a potion
+    pub(crate) fn new_daily(
+        raw_resource_pool: &[u8],
+        challenge: DailyChallenge,
+    ) -> Result<Self, GameError> {
+        let seeds = SeedInfo::derive(challenge.master_seed, challenge.player_id);
+        let (player_id, (x, y)) = (challenge.player_id, challenge.point);
+        let context = Self {
+            seeds,
+            daily: Some(challenge),
+            ranked: true,
+            ..Self::new(raw_resource_pool, seeds.map)?
+        };
+        context.create_session(player_id, x, y, &[])?;
+        Ok(context)
+    }
+
+    pub fn daily(&self) -> Option<&DailyChallenge> {
+        self.daily.as_ref()
+    }
+
+    pub fn seed_info(&self) -> SeedInfo {
+        self.seeds
+    }
+
+    pub fn resource_pool(&self) -> &[u8] {
+        &self.resource_pool
+    }
+
+    // unregistered, for throwaway games that `reset_all` never needs to reach
+    pub(crate) fn new(raw_resource_pool: &[u8], seed: u64) -> Result<Self, GameError> {
+        let (header, content) = unpack_resource_binary(raw_resource_pool)?;
+        let game = unwrap_result!(Game::new(&content.to_vec(), seed));
+        Ok(Self {
+            resource_pool: raw_resource_pool.to_vec(),
+            resource_pool_hash: header.hash,
+            seeds: SeedInfo::shared(seed),
+            state: Mutex::new(Arc::new(StateLock::new(State {
+                game: Some(game),
+                ..Default::default()
+            }))),
+            journal: StateLock::new(Journal {
+                seed,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn state(&self) -> Arc<StateLock<State>> {
+        // cloning the handle cannot leave it half-written, so a poisoned one is still sound
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn with_state<T>(
+        &self,
+        call: impl FnOnce(&mut State) -> Result<T, GameError>,
+    ) -> Result<T, GameError> {
+        loop {
+            let state = self.state();
+            let mut state = state.lock()?;
+            if !state.retired {
+                return call(&mut state);
+            }
+        }
+    }
+
+    // built when the pending battle first draws, from the journal's seed so a rebuilt game
+    // draws the same
+    fn battle_game<'a>(
+        &self,
+        battle_game: &'a mut Option<Game>,
+        battles: u64,
+    ) -> Result<&'a mut Game, GameError> {
+        if battle_game.is_none() {
+            let (_, content) = unpack_resource_binary(&self.resource_pool)?;
+            let battle = SeedInfo::battle_seed(self.journal.lock()?.seed);
+            let seed = SeedInfo::battle_stream(battle, battles);
+            *battle_game = Some(unwrap_result!(Game::new(&content.to_vec(), seed)));
+        }
+        battle_game.as_mut().ok_or(GameError::BattleNotTriggered)
+    }
+
+    fn into_state(self) -> Result<State, GameError> {
+        let state = self.state();
+        let mut state = state.lock()?;
+        Ok(std::mem::take(&mut *state))
+    }
+
+    fn register(self) -> Arc<Self> {
+        let context = Arc::new(self);
+        if let Ok(mut contexts) = CONTEXTS.lock() {
+            contexts.retain(|v| v.strong_count() > 0);
+            contexts.push(Arc::downgrade(&context));
+        }
+        context
+    }
+
+    // standalone battles run on a fresh game of the same pool and seed, journaled like any
+    // other game so they can be forked, snapshotted and replayed
+    pub fn create_standalone_battle(
+        &self,
+        warrior: WarriorContext,
+        deck: WarriorDeckContext,
+        enemies: Vec<Enemy>,
+    ) -> Result<Arc<Self>, GameError> {
+        let context = Self {
+            seeds: self.seeds,
+            ..Self::new(&self.resource_pool, self.journal.lock()?.seed)?
+        };
+        context.start_standalone_battle(warrior, deck, enemies)?;
+        Ok(context.register())
+    }
+
+    pub(crate) fn start_standalone_battle(
+        &self,
+        warrior: WarriorContext,
+        deck: WarriorDeckContext,
+        enemies: Vec<Enemy>,
+    ) -> Result<(), GameError> {
+        let action = Action::StandaloneBattle {
+            warrior: to_json(&warrior, "warrior")?,
+            deck: to_json(&deck, "warrior_deck")?,
+            enemies: to_json(&enemies, "enemies")?,
+        };
+        self.with_state(|state| {
+            // anywhere later in a journal it would hand the map session a warrior core never
+            // produced
+            if !self.journal.lock()?.actions.is_empty() {
+                return Err(GameError::InvalidSelection {
+                    subject: "standalone battle",
+                    reason: "it can only open a fresh game".to_owned(),
+                });
+            }
+            if state.battle.is_some() {
+                return Err(GameError::BattleAlreadyTriggered);
+            }
+            let battle = MapBattlePVE::create(warrior, deck, enemies).map_err(GameError::core)?;
+            state.battle = Some(battle);
+            state.battles += 1;
+            self.record(action, None)
+        })
+    }
+
+    fn digest<T: Serialize>(&self, output: &T) -> Result<Option<[u8; 32]>, GameError> {
+        if !self.journal.lock()?.recording {
+            return Ok(None);
+        }
+        let output = serde_json::to_vec(output).map_err(GameError::serialize("output"))?;
+        Ok(Some(blake2b_256(&output)))
+    }
+
+    fn record(&self, action: Action, digest: Option<[u8; 32]>) -> Result<(), GameError> {
+        let mut journal = self.journal.lock()?;
+        journal.actions.push(action);
+        journal.digests.push(digest);
+        drop(journal);
+        self.history.lock()?.checkpoint();
+        Ok(())
+    }
+
+    pub fn potion<R: Renderer>(&self, renderer: &R) -> Result<Option<R::Output>, GameError> {
+        self.with_state(|State { game, .. }| {
+            let game = unwrap_option!(game.as_ref());
+            game.potion
+                .as_ref()
+                .map(|potion| renderer.render(potion))
+                .transpose()
+        })
+    }
+
+    pub fn map_profile<R: Renderer>(&self, renderer: &R) -> Result<R::Output, GameError> {
+        self.with_state(|State { game, .. }| {
+            let game = unwrap_option!(game.as_ref());
+            renderer.render(&game.map)
+        })
+    }
+
+    pub fn warrior_profile<R: Renderer>(&self, renderer: &R) -> Result<R::Output, GameError> {
+        self.with_state(|State { warrior, .. }| {
+            let warrior = unwrap_option!(warrior.as_ref());
+            renderer.render(warrior)
+        })
+    }
+
+    pub fn deck_profile<R: Renderer>(&self, renderer: &R) -> Result<R::Output, GameError> {
+        self.with_state(|State { deck, .. }| {
+            let deck = unwrap_option!(deck.as_ref());
+            renderer.render(deck)
+        })
+    }
+
+    pub fn create_session(
+        &self,
+        player_id: u16,
+        point_x: u8,
+        point_y: u8,
+        raw_potion: &[u8],
+    ) -> Result<(), GameError> {
+        if self.seeds.player_id.is_some_and(|v| v != player_id) {
+            return Err(GameError::InvalidSelection {
+                subject: "player_id",
+                reason: "the seeds of this game were derived for another warrior".to_owned(),
+            });
+        }
+        self.with_state(|state| {
+            if state.warrior.is_some() || state.deck.is_some() {
+                return Err(GameError::AlreadyInitialized { subject: "session" });
+            }
+            let game = &mut state.game;
+            let game = unwrap_option!(game.as_mut());
+            let potion = if raw_potion.is_empty() {
+                None
+            } else {
+                Some(raw_potion.to_vec())
+            };
+            let point = Point {
+                x: point_x,
+                y: point_y,
+            };
+            let (warrior, deck) = unwrap_result!(game.new_session(player_id, point, potion));
+            state.warrior = Some(warrior);
+            state.deck = Some(deck);
+            reveal(state)?;
+            self.record(
+                Action::CreateSession {
+                    player_id,
+                    point: (point_x, point_y),
+                    potion: raw_potion.to_vec(),
+                },
+                None,
+            )
+        })
+    }
+
+    pub fn end_session(&self) -> Result<(), GameError> {
+        // a new session could start anywhere, so a daily run ends with its game
+        if self.daily.is_some() {
+            return Err(GameError::Ranked {
+                operation: "ending a daily session",
+            });
+        }
+        self.with_state(|state| {
+            release_session(state);
+            self.record(Action::EndSession, None)
+        })
+    }
+
+    pub fn peak_movement<R: Renderer>(
+        &self,
+        point_x: u8,
+        point_y: u8,
+        renderer: &R,
+    ) -> Result<Option<R::Output>, GameError> {
+        self.with_state(|State { game, warrior, .. }| {
+            let game = unwrap_option!(game.as_mut());
+            let warrior = unwrap_option!(warrior.as_mut());
+
+            let point = (point_x, point_y).into();
+            let node = unwrap_result!(game.map.peak_upcoming_movment(warrior, point));
+            node.map(|node| renderer.render(node)).transpose()
+        })
+    }
+
+    pub fn move_player<R: Renderer>(
+        &self,
+        point_x: u8,
+        point_y: u8,
+        selections: Vec<u8>,
+        renderer: &R,
+    ) -> Result<R::Output, GameError> {
+        self.with_state(|state| {
+            // checked before moving, a fight found afterwards would leave a move in the game
+            // that the journal never saw
+            if state.battle.is_some() {
+                return Err(GameError::BattleAlreadyTriggered);
+            }
+            let State {
+                game,
+                warrior,
+                deck,
+                battle: pending_battle,
+                battles,
+                ..
+            } = state;
+            let game = unwrap_option!(game.as_mut());
+            let warrior = unwrap_option!(warrior.as_mut());
+            let deck = unwrap_option!(deck.as_mut());
+            let point = (point_x, point_y).into();
+
+            let user_imported = selections.iter().map(|v| *v as usize).collect();
+            let move_result = unwrap_result!(game.map.move_to(
+                warrior,
+                deck,
+                point,
+                user_imported,
+                &mut game.controller,
+            ));
+            let rendered = renderer.render(&move_result);
+            let digest = self.digest(&move_result)?;
+            if let MoveResult::Fight(battle) = move_result {
+                *pending_battle = Some(battle);
+                *battles += 1;
+            }
+            reveal(state)?;
+            self.record(
+                Action::MovePlayer {
+                    point: (point_x, point_y),
+                    selections,
+                },
+                digest,
+            )?;
+            rendered
+        })
+    }
+
+    pub(crate) fn revealed(&self) -> Result<BTreeSet<(u8, u8)>, GameError> {
+        self.with_state(|state| Ok(state.revealed.clone()))
+    }
+
+    pub fn has_battle(&self) -> Result<bool, GameError> {
+        self.with_state(|state| Ok(state.battle.is_some()))
+    }
+
+    pub fn start_battle<R: Renderer>(&self, renderer: &R) -> Result<R::Output, GameError> {
+        self.start_battle_with(renderer, |_| {})
+    }
+
+    // `on_event` gets every log entry in order once the locks are released, so a listener
+    // may call back into the context
+    pub fn start_battle_with<R: Renderer>(
+        &self,
+        renderer: &R,
+        on_event: impl FnMut(BattleEvent),
+    ) -> Result<R::Output, GameError> {
+        let (result, events) = self.with_state(|state| {
+            let battle = state.battle.as_mut().ok_or(GameError::BattleNotTriggered)?;
+            let game = self.battle_game(&mut state.battle_game, state.battles)?;
+            let result = unwrap_result!(battle.start(&mut game.controller));
+            let digest = self.digest(&result)?;
+            self.record(Action::StartBattle, digest)?;
+            let events = self.battle_events(&result.1)?;
+            Ok((result, events))
+        })?;
+        events.into_iter().for_each(on_event);
+        renderer.render(&result)
+    }
+
+    pub fn iterate_battle<R: Renderer>(
+        &self,
+        operations: Vec<IterationInput>,
+        renderer: &R,
+    ) -> Result<R::Output, GameError> {
+        self.iterate_battle_with(operations, renderer, |_| {})
+    }
+
+    pub fn iterate_battle_with<R: Renderer>(
+        &self,
+        operations: Vec<IterationInput>,
+        renderer: &R,
+        on_event: impl FnMut(BattleEvent),
+    ) -> Result<R::Output, GameError> {
+        let (result, events) = self.with_state(|state| {
+            let battle = state.battle.as_mut().ok_or(GameError::BattleNotTriggered)?;
+            let game = self.battle_game(&mut state.battle_game, state.battles)?;
+            // journaled in their json form, core consumes the inputs
+            let recorded =
+                serde_json::to_value(&operations).map_err(GameError::serialize("operations"))?;
+            let result = unwrap_result!(battle.run(operations, &mut game.controller));
+            let digest = self.digest(&result)?;
+            self.record(
+                Action::IterateBattle {
+                    operations: recorded,
+                },
+                digest,
+            )?;
+            let events = self.battle_events(&result.1)?;
+            Ok((result, events))
+        })?;
+        events.into_iter().for_each(on_event);
+        renderer.render(&result)
+    }
+
+    fn battle_events<L: Serialize>(&self, logs: &[L]) -> Result<Vec<BattleEvent>, GameError> {
+        let turn = events::current_turn(&self.journal.lock()?.actions);
+        events::tag(turn, logs)
+    }
+
+    pub fn peak_target(&self, selection: Selection) -> Result<bool, GameError> {
+        self.with_state(|state| {
+            let battle = state.battle.as_mut().ok_or(GameError::BattleNotTriggered)?;
+            battle.peak_target(selection).map_err(GameError::core)
+        })
+    }
+
+    pub fn destroy_battle(&self) -> Result<(), GameError> {
+        self.with_state(|state| {
+            let battle = state.battle.take().ok_or(GameError::BattleNotTriggered)?;
+            state.battle_game = None;
+            let (warrior, deck, _) = unwrap_result!(battle.destroy());
+            state.settled.push((
+                serde_json::to_value(&warrior).map_err(GameError::serialize("warrior"))?,
+                serde_json::to_value(&deck).map_err(GameError::serialize("deck"))?,
+            ));
+            state.warrior = Some(warrior);
+            state.deck = Some(deck);
+            self.record(Action::DestroyBattle, None)
+        })
+    }
+
+    // a settled battle's DestroyBattle on a fork, which hands back what the real one left
+    fn settle(&self, settled: (serde_json::Value, serde_json::Value)) -> Result<(), GameError> {
+        let warrior = from_json(settled.0.clone(), "warrior")?;
+        let deck = from_json(settled.1.clone(), "warrior_deck")?;
+        self.with_state(|state| {
+            state.battle.take().ok_or(GameError::BattleNotTriggered)?;
+            state.battle_game = None;
+            state.settled.push(settled);
+            state.warrior = Some(warrior);
+            state.deck = Some(deck);
+            self.record(Action::DestroyBattle, None)
+        })
+    }
+
+    pub(crate) fn apply(&self, action: Action) -> Result<(), GameError> {
+        match action {
+            Action::CreateSession {
+                player_id,
+                point: (x, y),
+                potion,
+            } => self.create_session(player_id, x, y, &potion),
+            Action::MovePlayer {
+                point: (x, y),
+                selections,
+            } => self.move_player(x, y, selections, &Discard),
+            Action::StartBattle => self.start_battle(&Discard),
+            Action::IterateBattle { operations } => {
+                let operations = serde_json::from_value(operations)
+                    .map_err(GameError::invalid_selection("operations"))?;
+                self.iterate_battle(operations, &Discard)
+            }
+            Action::DestroyBattle => self.destroy_battle(),
+            Action::EndSession => self.end_session(),
+            Action::StandaloneBattle {
+                warrior,
+                deck,
+                enemies,
+            } => self.start_standalone_battle(
+                from_json(warrior, "warrior")?,
+                from_json(deck, "warrior_deck")?,
+                from_json(enemies, "enemies")?,
+            ),
+        }
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, GameError> {
+        self.with_state(|State { warrior, deck, .. }| {
+            let journal = self.journal.lock()?;
+            Ok(Snapshot {
+                resource_pool_hash: self.resource_pool_hash,
+                seed: journal.seed,
+                actions: journal.actions.clone(),
+                warrior: warrior
+                    .as_ref()
+                    .map(serde_json::to_value)
+                    .transpose()
+                    .map_err(GameError::serialize("warrior"))?,
+                deck: deck
+                    .as_ref()
+                    .map(serde_json::to_value)
+                    .transpose()
+                    .map_err(GameError::serialize("deck"))?,
+            })
+        })
+    }
+
+    pub fn export_snapshot(&self) -> Result<Vec<u8>, GameError> {
+        self.snapshot()?.encode()
+    }
+
+    // the game is rebuilt by replaying the journal from the seed, then checked against the
+    // recorded warrior and deck before it replaces the current state
+    pub fn import_snapshot(&self, bytes: &[u8]) -> Result<(), GameError> {
+        if self.ranked {
+            return Err(GameError::Ranked {
+                operation: "importing a snapshot",
+            });
+        }
+        let snapshot = Snapshot::decode(bytes)?;
+        if snapshot.resource_pool_hash != self.resource_pool_hash {
+            return Err(GameError::ResourcePoolMismatch {
+                payload: "snapshot",
+            });
+        }
+        let restored = self.rebuild(snapshot.seed, snapshot.actions)?;
+        let replayed = restored.snapshot()?;
+        if replayed.warrior != snapshot.warrior || replayed.deck != snapshot.deck {
+            return Err(GameError::Diverged {
+                payload: "snapshot",
+            });
+        }
+        let restored_journal = std::mem::take(&mut *restored.journal.lock()?);
+        let restored = restored.into_state()?;
+        self.with_state(|state| {
+            *state = restored;
+            let mut journal = self.journal.lock()?;
+            let recording = journal.recording;
+            *journal = restored_journal;
+            journal.recording = recording;
+            drop(journal);
+            self.history.lock()?.clear();
+            Ok(())
+        })
+    }
+
+    pub fn set_history_capacity(&self, capacity: usize) -> Result<(), GameError> {
+        if self.ranked {
+            return Err(GameError::Ranked {
+                operation: "history",
+            });
+        }
+        self.history.lock()?.set_capacity(capacity);
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> Result<bool, GameError> {
+        Ok(self.history.lock()?.undoable > 0)
+    }
+
+    pub fn can_redo(&self) -> Result<bool, GameError> {
+        Ok(!self.history.lock()?.redo.is_empty())
+    }
+
+    // drops the last journal action and rebuilds the game from what is left
+    pub fn undo(&self) -> Result<(), GameError> {
+        if self.ranked {
+            return Err(GameError::Ranked { operation: "undo" });
+        }
+        self.with_state(|state| {
+            if self.history.lock()?.undoable == 0 {
+                return Err(GameError::NothingToUndo);
+            }
+            let (seed, mut actions) = self.journal_actions()?;
+            let undone = actions.pop().ok_or(GameError::NothingToUndo)?;
+            *state = self.rebuild(seed, actions)?.into_state()?;
+            let mut journal = self.journal.lock()?;
+            journal.actions.pop();
+            journal.digests.pop();
+            drop(journal);
+            let mut history = self.history.lock()?;
+            history.undoable -= 1;
+            history.redo.push(undone);
+            Ok(())
+        })
+    }
+
+    pub fn redo(&self) -> Result<(), GameError> {
+        if self.ranked {
+            return Err(GameError::Ranked { operation: "redo" });
+        }
+        let mut redo = std::mem::take(&mut self.history.lock()?.redo);
+        let action = redo.pop().ok_or(GameError::NothingToRedo)?;
+        let result = self.apply(action.clone());
+        // applying records a fresh checkpoint, which drops the redo stack we still need
+        if result.is_err() {
+            redo.push(action);
+        }
+        self.history.lock()?.redo = redo;
+        result
+    }
+
+    pub fn set_replay_recording(&self, enabled: bool) -> Result<(), GameError> {
+        self.journal.lock()?.recording = enabled;
+        Ok(())
+    }
+
+    pub fn export_replay(&self) -> Result<Vec<u8>, GameError> {
+        let journal = self.journal.lock()?;
+        let player_id = journal.actions.iter().find_map(|action| match action {
+            Action::CreateSession { player_id, .. } => Some(*player_id),
+            _ => None,
+        });
+        let steps = journal
+            .actions
+            .iter()
+            .zip(&journal.digests)
+            .map(|(action, digest)| ReplayStep {
+                action: action.clone(),
+                digest: *digest,
+            })
+            .collect();
+        Replay {
+            resource_pool_hash: self.resource_pool_hash,
+            seed: journal.seed,
+            player_id,
+            steps,
+        }
+        .encode()
+    }
+
+    // runs a state-changing call and rolls the context back to its journal if core panics.
+    // wasm32 builds abort on panic, so there a panic traps the module instead: the lock it
+    // held stays taken and later calls get `Reentrant` until the client calls `recover`
+    #[cfg(target_arch = "wasm32")]
+    pub fn guarded<T>(&self, call: impl FnOnce() -> Result<T, GameError>) -> Result<T, GameError> {
+        call()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn guarded<T>(&self, call: impl FnOnce() -> Result<T, GameError>) -> Result<T, GameError> {
+        match panic::catch_unwind(AssertUnwindSafe(call)) {
+            Ok(result) => result,
+            Err(payload) => {
+                let reason = payload
+                    .downcast_ref::<&str>()
+                    .map(|v| v.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                self.recover()?;
+                Err(GameError::Panicked { reason })
+            }
+        }
+    }
+
+    // rebuilds every subsystem by replaying the journal into a fresh state lock, so neither a
+    // poisoned lock nor one a trapped call still holds can keep the game stuck
+    pub fn recover(&self) -> Result<(), GameError> {
+        self.journal.clear_poison();
+        self.history.clear_poison();
+        let stale = self.state();
+        stale.clear_poison();
+        // waits for calls in flight, a call that trapped on wasm32 never lets go and this
+        // gets `Reentrant` instead
+        let mut held = stale.lock().ok();
+        let (seed, actions) = self.journal_actions()?;
+        let rebuilt = self.rebuild(seed, actions)?.into_state()?;
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) =
+            Arc::new(StateLock::new(rebuilt));
+        if let Some(stale) = held.as_mut() {
+            stale.retired = true;
+        }
+        Ok(())
+    }
+
+    // read once per decision, every try builds its copy from it
+    pub(crate) fn fork(&self) -> Result<Fork<'_>, GameError> {
+        let (seed, actions) = self.journal_actions()?;
+        let settled = self.with_state(|state| Ok(state.settled.clone()))?;
+        Ok(Fork {
+            context: self,
+            seed,
+            actions,
+            settled,
+        })
+    }
+
+    pub(crate) fn journal_actions(&self) -> Result<(u64, Vec<Action>), GameError> {
+        let journal = self.journal.lock()?;
+        Ok((journal.seed, journal.actions.clone()))
+    }
+
+    fn rebuild(&self, seed: u64, actions: Vec<Action>) -> Result<Context, GameError> {
+        let rebuilt = Context {
+            seeds: self.seeds,
+            daily: self.daily.clone(),
+            ..Context::new(&self.resource_pool, seed)?
+        };
+        for action in actions {
+            rebuilt.apply(action)?;
+        }
+        Ok(rebuilt)
+    }
+
+    pub fn health(&self) -> Health {
+        let state = self.state();
+        Health::new(
+            SubsystemHealth::probe(&state, |v| v.game.is_some()),
+            SubsystemHealth::probe(&state, |v| v.warrior.is_some()),
+            SubsystemHealth::probe(&state, |v| v.deck.is_some()),
+            SubsystemHealth::probe(&state, |v| v.battle.is_some()),
+            SubsystemHealth::probe(&self.journal, |_| true),
+        )
+    }
+
+    pub fn release(&self) -> Result<(), GameError> {
+        self.with_state(|state| {
+            release_session(state);
+            state.game = None;
+            state.battles = 0;
+            state.settled.clear();
+            *self.journal.lock()? = Journal::default();
+            self.history.lock()?.clear();
+            Ok(())
+        })
+    }
+}
+
+// the journal of a game as it stood, to build unregistered copies to try inputs on. the
+// battles before the pending one are over, so a copy only moves the map through them and
+// takes the warrior and deck each one left, and replays the fight of the pending one alone
+pub(crate) struct Fork<'a> {
+    context: &'a Context,
+    seed: u64,
+    actions: Vec<Action>,
+    settled: Vec<(serde_json::Value, serde_json::Value)>,
+}
+
+impl Fork<'_> {
+    pub(crate) fn build(&self) -> Result<Context, GameError> {
+        let fork = Context {
+            seeds: self.context.seeds,
+            daily: self.context.daily.clone(),
+            ..Context::new(&self.context.resource_pool, self.seed)?
+        };
+        let pending = self
+            .actions
+            .iter()
+            .rposition(|action| matches!(action, Action::DestroyBattle))
+            .map_or(0, |last| last + 1);
+        let mut settled = self.settled.iter().cloned();
+        for (index, action) in self.actions.iter().cloned().enumerate() {
+            match action {
+                Action::StartBattle | Action::IterateBattle { .. } if index < pending => {
+                    fork.record(action, None)?
+                }
+                Action::DestroyBattle => {
+                    fork.settle(settled.next().ok_or(GameError::BattleNotTriggered)?)?
+                }
+                action => fork.apply(action)?,
+            }
+        }
+        Ok(fork)
+    }
+}
+
+// adds the map nodes core previews a move to from where the warrior now stands; a node core
+// refuses to preview is out of reach rather than an error
+fn reveal(state: &mut State) -> Result<(), GameError> {
+    let State {
+        game,
+        warrior,
+        revealed,
+        ..
+    } = state;
+    let game = unwrap_option!(game.as_mut());
+    let warrior = unwrap_option!(warrior.as_mut());
+    let profile = JsonRenderer.render(&game.map)?;
+    for (x, y) in map_nodes(&profile).into_keys() {
+        if let Ok(Some(_)) = game.map.peak_upcoming_movment(warrior, (x, y).into()) {
+            revealed.insert((x, y));
+        }
+    }
+    Ok(())
+}
+
+fn release_session(state: &mut State) {
+    state.revealed.clear();
+    state.battle = None;
+    state.battle_game = None;
+    state.deck = None;
+    state.warrior = None;
+}
+
+// re-executes a recorded replay against a fresh game and stops at the first step that
+// fails or produces a different output than the recorded one
+pub fn verify_replay(raw_resource_pool: &[u8], bytes: &[u8]) -> Result<ReplayReport, GameError> {
+    let replay = Replay::decode(bytes)?;
+    let (header, _) = unpack_resource_binary(raw_resource_pool)?;
+    if replay.resource_pool_hash != header.hash {
+        return Err(GameError::ResourcePoolMismatch { payload: "replay" });
+    }
+    let context = Context::new(raw_resource_pool, replay.seed)?;
+    context.set_replay_recording(true)?;
+    let mut report = ReplayReport {
+        executed_steps: 0,
+        total_steps: replay.steps.len(),
+        divergence: None,
+    };
+    for (step, ReplayStep { action, digest }) in replay.steps.into_iter().enumerate() {
+        let reason = match context.apply(action.clone()) {
+            Ok(()) => {
+                let journal = context.journal.lock()?;
+                let replayed = journal.digests.last().copied().flatten();
+                match digest {
+                    Some(expected) if replayed != Some(expected) => {
+                        Some("output differs from the recorded one".to_owned())
+                    }
+                    _ => None,
+                }
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(reason) = reason {
+            report.divergence = Some(Divergence {
+                step,
+                action,
+                reason,
+            });
+            break;
+        }
+        report.executed_steps += 1;
+    }
+    Ok(report)
+}
+
+// releases every live game even when some of them fail, those stay registered so a later
+// call can try them again
+pub fn reset_all() -> Result<(), GameError> {
+    let contexts = std::mem::take(&mut *CONTEXTS.lock()?);
+    let (mut failed, mut reasons) = (vec![], vec![]);
+    for context in contexts.into_iter().filter_map(|v| v.upgrade()) {
+        if let Err(e) = context.release() {
+            reasons.push(e.to_string());
+            failed.push(Arc::downgrade(&context));
+        }
+    }
+    if reasons.is_empty() {
+        return Ok(());
+    }
+    CONTEXTS.lock()?.extend(failed);
+    Err(GameError::ResetIncomplete { reasons })
+}
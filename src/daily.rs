@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::hash::blake2b_256;
+use crate::inspect::warrior_ids;
+use crate::navigation::{starting_points, trial_session};
+use crate::replay::Replay;
+use crate::resources::unpack_resource_binary;
+use crate::seed::SeedInfo;
+use crate::snapshot::Action;
+
+const DOMAIN: &[u8] = b"spore-warriors-daily";
+
+// everything a daily run starts from, the same for every player of that date
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DailyChallenge {
+    pub date: String,
+    pub master_seed: u64,
+    pub player_id: u16,
+    pub point: (u8, u8),
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunDigest {
+    pub date: String,
+    pub master_seed: u64,
+    pub player_id: u16,
+    pub steps: usize,
+    pub warrior: Option<Value>,
+    // blake2b over all of the above and every action of the run
+    pub digest: [u8; 32],
+}
+
+fn invalid_date(date: &str) -> GameError {
+    GameError::InvalidSelection {
+        subject: "date",
+        reason: format!("{date} is not a YYYY-MM-DD date"),
+    }
+}
+
+fn check_date(date: &str) -> Result<(), GameError> {
+    let parts: Vec<_> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(invalid_date(date));
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(invalid_date(date));
+    }
+    let number = |v: &str| v.parse::<u32>().map_err(|_| invalid_date(date));
+    let (year, month, day) = (number(year)?, number(month)?, number(day)?);
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return Err(invalid_date(date)),
+    };
+    if !(1..=days).contains(&day) {
+        return Err(invalid_date(date));
+    }
+    Ok(())
+}
+
+fn hash_of(date: &str) -> u64 {
+    let mut input = DOMAIN.to_vec();
+    input.extend(date.as_bytes());
+    let hash = blake2b_256(&input);
+    u64::from_le_bytes(hash[..8].try_into().expect("hash is 32 bytes"))
+}
+
+impl DailyChallenge {
+    // the warrior is picked among the pool's warriors and the starting point is the first of
+    // its starting areas from a date-derived offset where core accepts a session, tried on
+    // throwaway games
+    pub fn for_date(raw_resource_pool: &[u8], date: &str) -> Result<Self, GameError> {
+        check_date(date)?;
+        let master_seed = hash_of(date);
+        let warriors = warrior_ids(raw_resource_pool)?;
+        if warriors.is_empty() {
+            return Err(GameError::ResourcePool {
+                reason: "no warrior to play the daily challenge with".to_owned(),
+            });
+        }
+        let player_id = warriors[(master_seed % warriors.len() as u64) as usize];
+        let seeds = SeedInfo::derive(master_seed, player_id);
+        let map = Context::new(raw_resource_pool, seeds.map)?.map_profile(&JsonRenderer)?;
+        let points = starting_points(&map, player_id);
+        let offset = (master_seed % points.len().max(1) as u64) as usize;
+        for &point in points[offset..].iter().chain(&points[..offset]) {
+            match trial_session(raw_resource_pool, seeds.map, player_id, point, &[]) {
+                Ok(_) => {
+                    return Ok(Self {
+                        date: date.to_owned(),
+                        master_seed,
+                        player_id,
+                        point,
+                    })
+                }
+                Err(GameError::CoreError { .. }) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(GameError::ResourcePool {
+            reason: format!("no starting point accepts warrior {player_id}"),
+        })
+    }
+}
+
+// a ranked game with its session already started, undo and debug profiles stay locked
+pub fn create_daily_game(raw_resource_pool: &[u8], date: &str) -> Result<Arc<Context>, GameError> {
+    let challenge = DailyChallenge::for_date(raw_resource_pool, date)?;
+    Context::create_daily(raw_resource_pool, challenge)
+}
+
+pub fn run_digest(context: &Context) -> Result<RunDigest, GameError> {
+    let Some(challenge) = context.daily() else {
+        return Err(GameError::NotInitialized {
+            subject: "daily challenge",
+        });
+    };
+    let (_, actions) = context.journal_actions()?;
+    let warrior = context.warrior_profile(&JsonRenderer).ok();
+    let input = serde_json::to_vec(&(challenge, &actions, &warrior))
+        .map_err(GameError::serialize("run digest"))?;
+    Ok(RunDigest {
+        date: challenge.date.clone(),
+        master_seed: challenge.master_seed,
+        player_id: challenge.player_id,
+        steps: actions.len(),
+        warrior,
+        digest: blake2b_256(&input),
+    })
+}
+
+// what the leaderboard runs on a submitted replay, the digest it returns is the one to trust
+pub fn verify_daily_run(
+    raw_resource_pool: &[u8],
+    date: &str,
+    bytes: &[u8],
+) -> Result<RunDigest, GameError> {
+    let replay = Replay::decode(bytes)?;
+    let (header, _) = unpack_resource_binary(raw_resource_pool)?;
+    if replay.resource_pool_hash != header.hash {
+        return Err(GameError::ResourcePoolMismatch {
+            payload: "daily run",
+        });
+    }
+    let challenge = DailyChallenge::for_date(raw_resource_pool, date)?;
+    let context = Context::new_daily(raw_resource_pool, challenge)?;
+    let (seed, started) = context.journal_actions()?;
+    let recorded = serde_json::to_value(replay.steps.first().map(|v| &v.action));
+    let expected = serde_json::to_value(started.first());
+    if replay.seed != seed || recorded.ok() != expected.ok() {
+        return Err(GameError::InvalidPayload {
+            payload: "daily run",
+            reason: format!("the replay was not recorded on the {date} challenge"),
+        });
+    }
+    for (index, step) in replay.steps.into_iter().enumerate().skip(started.len()) {
+        // the session is already running, a daily run only moves on the map and fights
+        if !matches!(
+            step.action,
+            Action::MovePlayer { .. }
+                | Action::StartBattle
+                | Action::IterateBattle { .. }
+                | Action::DestroyBattle
+        ) {
+            return Err(GameError::InvalidPayload {
+                payload: "daily run",
+                reason: format!("step {index} is not an action a daily run records"),
+            });
+        }
+        context
+            .apply(step.action)
+            .map_err(|_| GameError::Diverged {
+                payload: "daily run",
+            })?;
+    }
+    run_digest(&context)
+}
@@ -0,0 +1,134 @@
+use std::fmt::{Debug, Display};
+use std::sync::PoisonError;
+
+use serde::Serialize;
+use thiserror::Error;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::JsValue;
+
+// surfaced to js as `{ kind, message, ...context }` so the client can branch on `kind`
+#[derive(Debug, Error, Serialize)]
+#[serde(tag = "kind")]
+pub enum GameError {
+    #[error("{subject} is not initialized")]
+    NotInitialized { subject: &'static str },
+    #[error("{subject} has already been initialized")]
+    AlreadyInitialized { subject: &'static str },
+    #[error("no battle triggered from map")]
+    BattleNotTriggered,
+    #[error("battle already triggered from map")]
+    BattleAlreadyTriggered,
+    #[error("invalid {subject}: {reason}")]
+    InvalidSelection {
+        subject: &'static str,
+        reason: String,
+    },
+    #[error("core error {code}: {reason}")]
+    CoreError { code: String, reason: String },
+    #[error("cannot deserialize {subject}: {reason}")]
+    Deserialize {
+        subject: &'static str,
+        reason: String,
+    },
+    #[error("cannot serialize {subject}: {reason}")]
+    Serialize {
+        subject: &'static str,
+        reason: String,
+    },
+    #[error("invalid {payload}: {reason}")]
+    InvalidPayload {
+        payload: &'static str,
+        reason: String,
+    },
+    #[error("unsupported {payload} version {found}, expected {expected}")]
+    UnsupportedVersion {
+        payload: &'static str,
+        found: u16,
+        expected: u16,
+    },
+    #[error("{payload} was made against a different resource pool")]
+    ResourcePoolMismatch { payload: &'static str },
+    #[error("{payload} replay diverged from the recorded state")]
+    Diverged { payload: &'static str },
+    #[error("resource pool error: {reason}")]
+    ResourcePool { reason: String },
+    #[error("state lock poisoned: {reason}")]
+    Poisoned { reason: String },
+    #[error("game state was locked again by the call already holding it")]
+    Reentrant,
+    #[error("core panicked: {reason}")]
+    Panicked { reason: String },
+    #[error("{} games could not be released: {}", .reasons.len(), .reasons.join("; "))]
+    ResetIncomplete { reasons: Vec<String> },
+    #[error("{operation} is disabled in ranked games")]
+    Ranked { operation: &'static str },
+    #[error("nothing to undo")]
+    NothingToUndo,
+    #[error("nothing to redo")]
+    NothingToRedo,
+}
+
+impl GameError {
+    // core errors carry no numeric code, so the variant name from `Debug` stands in for it
+    pub fn core<E: Debug + Display>(error: E) -> Self {
+        let debug = format!("{error:?}");
+        let code = debug
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        GameError::CoreError {
+            code,
+            reason: error.to_string(),
+        }
+    }
+
+    pub fn deserialize<E: Display>(subject: &'static str) -> impl FnOnce(E) -> Self {
+        move |error| GameError::Deserialize {
+            subject,
+            reason: error.to_string(),
+        }
+    }
+
+    pub fn serialize<E: Display>(subject: &'static str) -> impl FnOnce(E) -> Self {
+        move |error| GameError::Serialize {
+            subject,
+            reason: error.to_string(),
+        }
+    }
+
+    pub fn invalid_selection<E: Display>(subject: &'static str) -> impl FnOnce(E) -> Self {
+        move |error| GameError::InvalidSelection {
+            subject,
+            reason: error.to_string(),
+        }
+    }
+}
+
+impl<T> From<PoisonError<T>> for GameError {
+    fn from(error: PoisonError<T>) -> Self {
+        GameError::Poisoned {
+            reason: error.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl From<GameError> for JsValue {
+    fn from(error: GameError) -> Self {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            #[serde(flatten)]
+            error: &'a GameError,
+            message: String,
+        }
+
+        let payload = Payload {
+            error: &error,
+            message: error.to_string(),
+        };
+        payload
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap_or_else(|e| e.into())
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+use spore_warriors_core::battle::traits::IterationInput;
+
+use crate::error::GameError;
+use crate::snapshot::Action;
+
+// `Unknown` is any log whose variant names no side, rather than a guess at one
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum BattleActor {
+    Warrior,
+    Enemy(Option<usize>),
+    Unknown,
+}
+
+// one battle log entry, `turn` counts the turns the warrior ended since the battle started,
+// those of the step that produced it included, and `kind` is the variant name of core's log enum, exactly as core serializes it. core hands
+// back a step's logs all at once, so listeners receive them in order right after the step
+// that produced them, not while it is still running
+#[derive(Debug, Serialize, Clone)]
+pub struct BattleEvent {
+    pub turn: usize,
+    pub sequence: usize,
+    pub kind: String,
+    pub actor: BattleActor,
+    pub log: serde_json::Value,
+}
+
+// core's log enum is externally tagged, so its variant is the string itself or the one key
+fn kind_of(log: &serde_json::Value) -> Option<(&str, Option<&serde_json::Value>)> {
+    match log {
+        serde_json::Value::String(variant) => Some((variant.as_str(), None)),
+        serde_json::Value::Object(object) if object.len() == 1 => object
+            .iter()
+            .next()
+            .map(|(variant, payload)| (variant.as_str(), Some(payload))),
+        _ => None,
+    }
+}
+
+// the side a log belongs to as its variant names it, `Warrior..` or `Enemy..`; an enemy
+// index is only taken from a payload that is nothing but that index
+fn actor_of(variant: &str, payload: Option<&serde_json::Value>) -> BattleActor {
+    if variant.starts_with("Enemy") {
+        let index = payload.and_then(serde_json::Value::as_u64);
+        BattleActor::Enemy(index.map(|v| v as usize))
+    } else if variant.starts_with("Warrior") {
+        BattleActor::Warrior
+    } else {
+        BattleActor::Unknown
+    }
+}
+
+// the `EndTurn` inputs journaled since the last `StartBattle`, card plays leave it as it is
+pub fn current_turn(actions: &[Action]) -> usize {
+    actions
+        .iter()
+        .rev()
+        .take_while(|action| !matches!(action, Action::StartBattle))
+        .filter_map(|action| match action {
+            Action::IterateBattle { operations } => {
+                serde_json::from_value::<Vec<IterationInput>>(operations.clone()).ok()
+            }
+            _ => None,
+        })
+        .flatten()
+        .filter(|input| matches!(input, IterationInput::EndTurn))
+        .count()
+}
+
+pub fn tag<L: Serialize>(turn: usize, logs: &[L]) -> Result<Vec<BattleEvent>, GameError> {
+    logs.iter()
+        .enumerate()
+        .map(|(sequence, log)| {
+            let log = serde_json::to_value(log).map_err(GameError::serialize("log"))?;
+            let (kind, actor) = match kind_of(&log) {
+                Some((variant, payload)) => (variant.to_string(), actor_of(variant, payload)),
+                None => (String::new(), BattleActor::Unknown),
+            };
+            Ok(BattleEvent {
+                turn,
+                sequence,
+                kind,
+                actor,
+                log,
+            })
+        })
+        .collect()
+}
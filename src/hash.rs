@@ -0,0 +1,13 @@
+use blake2b_simd::Params;
+
+const PERSONALIZATION: &[u8] = b"ckb-default-hash";
+
+pub fn blake2b_256(data: &[u8]) -> [u8; 32] {
+    let hash = Params::new()
+        .hash_length(32)
+        .personal(PERSONALIZATION)
+        .hash(data);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(hash.as_bytes());
+    result
+}
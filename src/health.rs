@@ -0,0 +1,60 @@
+use std::sync::TryLockError;
+
+use serde::Serialize;
+
+use crate::lock::StateLock;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum SubsystemHealth {
+    Ready,
+    Empty,
+    Busy,
+    Poisoned,
+}
+
+impl SubsystemHealth {
+    pub(crate) fn probe<T>(lock: &StateLock<T>, ready: impl FnOnce(&T) -> bool) -> Self {
+        match lock.try_lock() {
+            Ok(value) if ready(&value) => SubsystemHealth::Ready,
+            Ok(_) => SubsystemHealth::Empty,
+            Err(TryLockError::WouldBlock) => SubsystemHealth::Busy,
+            Err(TryLockError::Poisoned(_)) => SubsystemHealth::Poisoned,
+        }
+    }
+
+    pub fn is_consistent(self) -> bool {
+        !matches!(self, SubsystemHealth::Busy | SubsystemHealth::Poisoned)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Health {
+    pub game: SubsystemHealth,
+    pub warrior: SubsystemHealth,
+    pub deck: SubsystemHealth,
+    pub battle: SubsystemHealth,
+    pub journal: SubsystemHealth,
+    pub consistent: bool,
+}
+
+impl Health {
+    pub fn new(
+        game: SubsystemHealth,
+        warrior: SubsystemHealth,
+        deck: SubsystemHealth,
+        battle: SubsystemHealth,
+        journal: SubsystemHealth,
+    ) -> Self {
+        let consistent = [game, warrior, deck, battle, journal]
+            .into_iter()
+            .all(SubsystemHealth::is_consistent);
+        Health {
+            game,
+            warrior,
+            deck,
+            battle,
+            journal,
+            consistent,
+        }
+    }
+}
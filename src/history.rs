@@ -0,0 +1,32 @@
+use crate::snapshot::Action;
+
+// every journal action is one undo step, undoing rebuilds the game from the shorter journal
+// and keeps the dropped action around for redo
+#[derive(Default)]
+pub struct History {
+    pub capacity: usize,
+    pub undoable: usize,
+    pub redo: Vec<Action>,
+}
+
+impl History {
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.undoable = self.undoable.min(capacity);
+        if capacity == 0 {
+            self.redo.clear();
+        }
+    }
+
+    pub fn checkpoint(&mut self) {
+        if self.capacity > 0 {
+            self.undoable = (self.undoable + 1).min(self.capacity);
+            self.redo.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undoable = 0;
+        self.redo.clear();
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use molecule::prelude::Reader;
+use molecule::{unpack_number, NUMBER_SIZE};
+use serde::Serialize;
+use serde_json::Value;
+use spore_warriors_generated::ResourcePoolReader;
+
+use crate::error::GameError;
+use crate::lint::POOLS;
+use crate::resources::{unpack_resource_binary, ResourceHeader};
+
+// the pools and the ids of their entries are read with spore-warriors-generated, the schema
+// core reads them with. the other fields are listed from the molecule layout for display
+// alone: fields of 1, 2, 4 or 8 bytes become numbers, tables and vectors lists and anything
+// else hex
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PoolEntry {
+    pub id: u64,
+    pub fields: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolSummary {
+    pub pool: &'static str,
+    pub count: usize,
+    pub ids: Vec<u64>,
+    pub entries: Vec<PoolEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceInspection {
+    pub header: ResourceHeader,
+    pub size: usize,
+    pub pools: Vec<PoolSummary>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum EntryChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryDiff {
+    pub pool: &'static str,
+    pub id: u64,
+    pub change: EntryChange,
+    pub before: Option<PoolEntry>,
+    pub after: Option<PoolEntry>,
+}
+
+fn number(bytes: &[u8]) -> usize {
+    unpack_number(bytes) as usize
+}
+
+// splits a molecule table or dynvec into its items, `None` when `bytes` is not laid out as one
+fn items(bytes: &[u8]) -> Option<Vec<&[u8]>> {
+    if bytes.len() < NUMBER_SIZE || number(bytes) != bytes.len() {
+        return None;
+    }
+    if bytes.len() == NUMBER_SIZE {
+        return Some(vec![]);
+    }
+    let first = number(bytes.get(NUMBER_SIZE..NUMBER_SIZE * 2)?);
+    if !first.is_multiple_of(NUMBER_SIZE) || first < NUMBER_SIZE * 2 || first > bytes.len() {
+        return None;
+    }
+    let mut offsets: Vec<_> = bytes[NUMBER_SIZE..first]
+        .chunks(NUMBER_SIZE)
+        .map(number)
+        .collect();
+    offsets.push(bytes.len());
+    if offsets.windows(2).any(|v| v[0] > v[1]) {
+        return None;
+    }
+    Some(offsets.windows(2).map(|v| &bytes[v[0]..v[1]]).collect())
+}
+
+// fixed size items behind an item count, as molecule lays out `Bytes` and fixvecs
+fn fixed_items(bytes: &[u8]) -> Option<Vec<&[u8]>> {
+    let count = number(bytes.get(..NUMBER_SIZE)?);
+    let body = &bytes[NUMBER_SIZE..];
+    if count == 0 || body.is_empty() || !body.len().is_multiple_of(count) {
+        return None;
+    }
+    Some(body.chunks(body.len() / count).collect())
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    bytes
+        .iter()
+        .map(|v| format!("{v:02x}"))
+        .collect::<String>()
+        .into()
+}
+
+// the largest integer a js number holds exactly
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+// every short field is read as a little-endian number, an empty table or vector included,
+// since its four bytes could be either; numbers js cannot hold exactly become strings
+fn field(bytes: &[u8]) -> Value {
+    if matches!(bytes.len(), 1 | 2 | 4 | 8) {
+        let mut number = [0u8; 8];
+        number[..bytes.len()].copy_from_slice(bytes);
+        return match u64::from_le_bytes(number) {
+            number if number > MAX_SAFE_INTEGER => number.to_string().into(),
+            number => number.into(),
+        };
+    }
+    if let Some(items) = items(bytes) {
+        return items.into_iter().map(field).collect();
+    }
+    match fixed_items(bytes) {
+        // byte vectors are most likely names and descriptions
+        Some(items) if items[0].len() == 1 => match std::str::from_utf8(&bytes[NUMBER_SIZE..]) {
+            Ok(text) => text.into(),
+            Err(_) => hex(&bytes[NUMBER_SIZE..]),
+        },
+        Some(items) => items.into_iter().map(field).collect(),
+        None => hex(bytes),
+    }
+}
+
+// an entry with the little-endian id its reader gave
+fn entry(bytes: &[u8], id: &[u8]) -> PoolEntry {
+    let fields: Vec<_> = match items(bytes) {
+        Some(items) => items.into_iter().map(field).collect(),
+        None => vec![field(bytes)],
+    };
+    let mut number = [0u8; 8];
+    number[..id.len().min(8)].copy_from_slice(&id[..id.len().min(8)]);
+    PoolEntry {
+        id: u64::from_le_bytes(number),
+        fields,
+    }
+}
+
+// every pool reader lists entries that carry an `id`
+macro_rules! pool_entries {
+    ($pool:expr) => {
+        $pool
+            .iter()
+            .map(|v| entry(v.as_slice(), v.id().as_slice()))
+            .collect::<Vec<_>>()
+    };
+}
+
+fn invalid(reason: impl Into<String>) -> GameError {
+    GameError::InvalidPayload {
+        payload: "resource binary",
+        reason: reason.into(),
+    }
+}
+
+pub fn inspect_resource_binary(bytes: &[u8]) -> Result<ResourceInspection, GameError> {
+    let (header, content) = unpack_resource_binary(bytes)?;
+    let reader = ResourcePoolReader::from_slice(content).map_err(|e| invalid(e.to_string()))?;
+    let entries = [
+        pool_entries!(reader.action_pool()),
+        pool_entries!(reader.card_pool()),
+        pool_entries!(reader.system_pool()),
+        pool_entries!(reader.enemy_pool()),
+        pool_entries!(reader.loot_pool()),
+        pool_entries!(reader.scene_pool()),
+        pool_entries!(reader.warrior_pool()),
+    ];
+    let pools = POOLS
+        .into_iter()
+        .zip(entries)
+        .map(|(pool, entries)| PoolSummary {
+            pool,
+            count: entries.len(),
+            ids: entries.iter().map(|v| v.id).collect(),
+            entries,
+        })
+        .collect();
+    Ok(ResourceInspection {
+        header,
+        size: content.len(),
+        pools,
+    })
+}
+
+// the ids of the warrior pool, the values `create_session` takes as `player_id`
+pub(crate) fn warrior_ids(bytes: &[u8]) -> Result<Vec<u16>, GameError> {
+    let (_, content) = unpack_resource_binary(bytes)?;
+    let reader = ResourcePoolReader::from_slice(content).map_err(|e| invalid(e.to_string()))?;
+    pool_entries!(reader.warrior_pool())
+        .iter()
+        .map(|v| {
+            u16::try_from(v.id).map_err(|_| GameError::ResourcePool {
+                reason: format!("warrior id {} does not fit a player id", v.id),
+            })
+        })
+        .collect()
+}
+
+// entries are matched by id
+fn keyed(entries: Vec<PoolEntry>) -> BTreeMap<u64, PoolEntry> {
+    entries.into_iter().map(|entry| (entry.id, entry)).collect()
+}
+
+pub fn diff_resource_binaries(before: &[u8], after: &[u8]) -> Result<Vec<EntryDiff>, GameError> {
+    let before = inspect_resource_binary(before)?;
+    let after = inspect_resource_binary(after)?;
+    let mut diffs = vec![];
+    for (old, new) in before.pools.into_iter().zip(after.pools) {
+        let mut old_entries = keyed(old.entries);
+        for (key, new_entry) in keyed(new.entries) {
+            let (change, old_entry) = match old_entries.remove(&key) {
+                Some(old_entry) if old_entry == new_entry => continue,
+                Some(old_entry) => (EntryChange::Changed, Some(old_entry)),
+                None => (EntryChange::Added, None),
+            };
+            diffs.push(EntryDiff {
+                pool: new.pool,
+                id: key,
+                change,
+                before: old_entry,
+                after: Some(new_entry),
+            });
+        }
+        diffs.extend(old_entries.into_iter().map(|(key, old_entry)| EntryDiff {
+            pool: old.pool,
+            id: key,
+            change: EntryChange::Removed,
+            before: Some(old_entry),
+            after: None,
+        }));
+    }
+    Ok(diffs)
+}
@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+use spore_warriors_resources::parse_to_binary;
+
+use crate::navigation::{map_nodes, starting_warriors, STARTING_WARRIORS};
+
+pub const POOLS: [&str; 7] = [
+    "action", "card", "system", "enemy", "loot", "scene", "warrior",
+];
+
+// the fields holding ids of another pool, per pool and at any depth of an entry; references
+// kept in fields not listed are left to spore-warriors-resources, which gets the final word on
+// every build
+const REFERENCES: [(&str, &[(&str, &str)]); 7] = [
+    ("action", &[]),
+    ("card", &[("action", "action")]),
+    ("system", &[]),
+    ("enemy", &[("actions", "action")]),
+    ("loot", &[("cards", "card")]),
+    (
+        "scene",
+        &[
+            ("enemies", "enemy"),
+            ("loot", "loot"),
+            (STARTING_WARRIORS, "warrior"),
+        ],
+    ),
+    ("warrior", &[("deck", "card"), ("charactor_card", "card")]),
+];
+
+fn referenced_pool(pool: &str, field: &str) -> Option<&'static str> {
+    REFERENCES
+        .into_iter()
+        .find(|(name, _)| *name == pool)
+        .and_then(|(_, fields)| fields.iter().find(|(name, _)| *name == field))
+        .map(|(_, target)| *target)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Unparsable,
+    MissingId,
+    DuplicateId,
+    DanglingReference,
+    EnemyWithoutActions,
+    // no node of the scene lets any known warrior start in it
+    NoStartingArea,
+    // spore-warriors-resources refused the pools, whatever else was found
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub pool: Option<&'static str>,
+    pub id: Option<u64>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(
+        kind: DiagnosticKind,
+        pool: &'static str,
+        id: Option<u64>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            pool: Some(pool),
+            id,
+            message: message.into(),
+        }
+    }
+}
+
+// a pool file is an object holding a single list of entries, e.g. `{ "cards": [..] }`
+fn entries(document: &Value) -> &[Value] {
+    document
+        .as_object()
+        .and_then(|object| object.values().find_map(Value::as_array))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn references(pool: &str, value: &Value, found: &mut Vec<(&'static str, u64)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (referenced_pool(pool, key), value) {
+                    (Some(pool), Value::Number(id)) => {
+                        found.extend(id.as_u64().map(|id| (pool, id)))
+                    }
+                    (Some(pool), Value::Array(ids)) if ids.iter().all(Value::is_number) => {
+                        found.extend(ids.iter().filter_map(Value::as_u64).map(|id| (pool, id)))
+                    }
+                    _ => references(pool, value, found),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| references(pool, v, found)),
+        _ => {}
+    }
+}
+
+fn has_actions(enemy: &Value) -> bool {
+    let mut found = vec![];
+    references("enemy", enemy, &mut found);
+    found.iter().any(|(pool, _)| *pool == "action")
+}
+
+// checks the seven pools as a whole and reports every problem found instead of the first
+pub fn validate_resource_pools(
+    action_pool: &str,
+    card_pool: &str,
+    system_pool: &str,
+    enemy_pool: &str,
+    loot_pool: &str,
+    scene_pool: &str,
+    warrior_pool: &str,
+) -> Vec<Diagnostic> {
+    let sources = [
+        action_pool,
+        card_pool,
+        system_pool,
+        enemy_pool,
+        loot_pool,
+        scene_pool,
+        warrior_pool,
+    ];
+    let mut diagnostics = vec![];
+    let mut documents = vec![];
+    for (pool, source) in POOLS.into_iter().zip(sources) {
+        match serde_json::from_str::<Value>(source) {
+            Ok(document) => documents.push((pool, document)),
+            Err(e) => diagnostics.push(Diagnostic::new(
+                DiagnosticKind::Unparsable,
+                pool,
+                None,
+                e.to_string(),
+            )),
+        }
+    }
+
+    let mut ids: BTreeMap<&str, BTreeSet<u64>> = BTreeMap::new();
+    for &(pool, ref document) in &documents {
+        let known = ids.entry(pool).or_default();
+        for (index, entry) in entries(document).iter().enumerate() {
+            let Some(id) = entry.get("id").and_then(Value::as_u64) else {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MissingId,
+                    pool,
+                    None,
+                    format!("entry #{index} has no numeric id"),
+                ));
+                continue;
+            };
+            if !known.insert(id) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::DuplicateId,
+                    pool,
+                    Some(id),
+                    format!("{pool} {id} is defined more than once"),
+                ));
+            }
+        }
+    }
+
+    for &(pool, ref document) in &documents {
+        for entry in entries(document) {
+            let id = entry.get("id").and_then(Value::as_u64);
+            let mut found = vec![];
+            references(pool, entry, &mut found);
+            for (target, target_id) in found {
+                // a pool that failed to parse already has its own diagnostic
+                let Some(known) = ids.get(target) else {
+                    continue;
+                };
+                if !known.contains(&target_id) {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::DanglingReference,
+                        pool,
+                        id,
+                        format!("refers to {target} {target_id}, which does not exist"),
+                    ));
+                }
+            }
+            if pool == "enemy" && !has_actions(entry) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::EnemyWithoutActions,
+                    pool,
+                    id,
+                    "enemy has no actions to take",
+                ));
+            }
+            // starting areas are read the way the game looks for them on the map
+            if pool == "scene" {
+                let warriors = ids.get("warrior");
+                let enterable = map_nodes(entry).values().any(|node| {
+                    starting_warriors(node)
+                        .iter()
+                        .any(|warrior| warriors.is_none_or(|v| v.contains(warrior)))
+                });
+                if !enterable {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::NoStartingArea,
+                        pool,
+                        id,
+                        "no node lets a known warrior start in this scene",
+                    ));
+                }
+            }
+        }
+    }
+
+    // a pool that is not even json already says why it cannot be built
+    if documents.len() == POOLS.len() {
+        if let Err(e) = parse_to_binary(
+            action_pool,
+            card_pool,
+            system_pool,
+            enemy_pool,
+            loot_pool,
+            scene_pool,
+            warrior_pool,
+        ) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Rejected,
+                pool: None,
+                id: None,
+                message: e.to_string(),
+            });
+        }
+    }
+    diagnostics
+}
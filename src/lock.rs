@@ -0,0 +1,79 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockResult};
+
+use crate::error::GameError;
+
+thread_local! {
+    static THREAD: u8 = const { 0 };
+}
+
+// an address no other live thread shares, cheaper than `ThreadId` and never zero
+fn current_thread() -> usize {
+    THREAD.with(|v| v as *const u8 as usize)
+}
+
+// a blocking mutex that remembers which thread holds it, so calls from other threads wait
+// their turn while a thread locking it a second time gets `Reentrant` instead of a deadlock
+#[derive(Default)]
+pub struct StateLock<T> {
+    value: Mutex<T>,
+    holder: AtomicUsize,
+}
+
+pub struct StateGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    holder: &'a AtomicUsize,
+}
+
+impl<T> StateLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Mutex::new(value),
+            holder: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn lock(&self) -> Result<StateGuard<'_, T>, GameError> {
+        let thread = current_thread();
+        if self.holder.load(Ordering::Acquire) == thread {
+            return Err(GameError::Reentrant);
+        }
+        let guard = self.value.lock()?;
+        self.holder.store(thread, Ordering::Release);
+        Ok(StateGuard {
+            guard,
+            holder: &self.holder,
+        })
+    }
+
+    // for health probes, which must not wait on a busy subsystem
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        self.value.try_lock()
+    }
+
+    pub fn clear_poison(&self) {
+        self.value.clear_poison();
+    }
+}
+
+impl<T> Drop for StateGuard<'_, T> {
+    // runs before the inner guard unlocks, so no other thread ever sees a stale holder
+    fn drop(&mut self) {
+        self.holder.store(0, Ordering::Release);
+    }
+}
+
+impl<T> Deref for StateGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for StateGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
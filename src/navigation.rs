@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::snapshot::Action;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReachablePoint {
+    pub point: (u8, u8),
+    pub node: Value,
+}
+
+// the map laid out for text views; `columns` and `rows` come from the profile, or else reach
+// as far as its nodes do
+#[derive(Debug, Clone)]
+pub struct MapLayout {
+    pub columns: u16,
+    pub rows: u16,
+    pub position: Option<(u8, u8)>,
+    pub nodes: BTreeMap<(u8, u8), Value>,
+}
+
+pub(crate) fn point_of(value: &Value) -> Option<(u8, u8)> {
+    let (x, y) = match value {
+        Value::Object(point) => (point.get("x")?, point.get("y")?),
+        Value::Array(point) if point.len() == 2 => (&point[0], &point[1]),
+        _ => return None,
+    };
+    Some((x.as_u64()?.try_into().ok()?, y.as_u64()?.try_into().ok()?))
+}
+
+// the nodes the map data holds, any object carrying a `point`, keyed by that point
+pub(crate) fn map_nodes(profile: &Value) -> BTreeMap<(u8, u8), &Value> {
+    fn collect<'a>(value: &'a Value, nodes: &mut BTreeMap<(u8, u8), &'a Value>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(point) = object.get("point").and_then(point_of) {
+                    nodes.insert(point, value);
+                    return;
+                }
+                object.values().for_each(|v| collect(v, nodes));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect(v, nodes)),
+            _ => {}
+        }
+    }
+    let mut nodes = BTreeMap::new();
+    collect(profile, &mut nodes);
+    nodes
+}
+
+// where the warrior stands, replayed from the journal
+pub(crate) fn position(actions: &[Action]) -> Option<(u8, u8)> {
+    actions.iter().fold(None, |position, action| match action {
+        Action::CreateSession { point, .. } | Action::MovePlayer { point, .. } => Some(*point),
+        Action::EndSession => None,
+        _ => position,
+    })
+}
+
+pub fn map_layout(context: &Context) -> Result<MapLayout, GameError> {
+    let profile = context.map_profile(&JsonRenderer)?;
+    let nodes: BTreeMap<_, _> = map_nodes(&profile)
+        .into_iter()
+        .map(|(point, node)| (point, node.clone()))
+        .collect();
+    let size = |key: &str, axis: fn(&(u8, u8)) -> u8| {
+        profile
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(|v| v.min(u8::MAX as u64 + 1) as u16)
+            .unwrap_or_else(|| nodes.keys().map(axis).max().map_or(0, |v| v as u16 + 1))
+    };
+    let (columns, rows) = (size("width", |v| v.0), size("height", |v| v.1));
+    let (_, actions) = context.journal_actions()?;
+    Ok(MapLayout {
+        columns,
+        rows,
+        position: position(&actions),
+        nodes,
+    })
+}
+
+// every node point of the map, the only places a session can start or a move can end
+pub(crate) fn map_area(context: &Context) -> Result<Vec<(u8, u8)>, GameError> {
+    Ok(map_nodes(&context.map_profile(&JsonRenderer)?)
+        .into_keys()
+        .collect())
+}
+
+// the field of a scene node listing the warriors that may start on it, as scene pools
+// declare their starting areas
+pub(crate) const STARTING_WARRIORS: &str = "warriors";
+
+// the warriors a node lets start on it, none for any node that is no starting area
+pub(crate) fn starting_warriors(node: &Value) -> Vec<u64> {
+    node[STARTING_WARRIORS]
+        .as_array()
+        .map(|ids| ids.iter().filter_map(Value::as_u64).collect())
+        .unwrap_or_default()
+}
+
+// nodes of the map data that let `player_id` start a session on them
+pub(crate) fn starting_points(profile: &Value, player_id: u16) -> Vec<(u8, u8)> {
+    map_nodes(profile)
+        .into_iter()
+        .filter(|(_, node)| starting_warriors(node).contains(&(player_id as u64)))
+        .map(|(point, _)| point)
+        .collect()
+}
+
+// a throwaway game with a session started at `point`, core refusing it means the warrior
+// cannot start there or the potion does not fit
+pub(crate) fn trial_session(
+    raw_resource_pool: &[u8],
+    seed: u64,
+    player_id: u16,
+    (x, y): (u8, u8),
+    raw_potion: &[u8],
+) -> Result<Context, GameError> {
+    let context = Context::new(raw_resource_pool, seed)?;
+    context.create_session(player_id, x, y, raw_potion)?;
+    Ok(context)
+}
+
+// every map node the warrior can move to next; a node core refuses to preview counts as out
+// of reach instead of failing the whole query
+pub fn reachable_points(context: &Context) -> Result<Vec<ReachablePoint>, GameError> {
+    let mut points = vec![];
+    for (x, y) in map_area(context)? {
+        match context.peak_movement(x, y, &JsonRenderer) {
+            Ok(Some(node)) => points.push(ReachablePoint {
+                point: (x, y),
+                node,
+            }),
+            Ok(None) | Err(GameError::CoreError { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(points)
+}
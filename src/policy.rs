@@ -0,0 +1,289 @@
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use spore_warriors_core::battle::traits::{FightLog, IterationInput, IterationOutput, Selection};
+
+use crate::context::{Context, Fork, JsonRenderer};
+use crate::error::GameError;
+use crate::events::BattleEvent;
+
+const MAX_PLAYS_PER_TURN: usize = 10;
+const MAX_HAND: usize = 10;
+const MAX_ENEMIES: usize = 8;
+// how many of its best first plays `Lookahead` weighs follow-ups for
+const LOOKAHEAD_BEAM: usize = 3;
+
+// json shapes of the core battle enums, kept together since nothing else in the crate
+// builds battle inputs by itself
+pub fn end_turn() -> Value {
+    json!(["EndTurn"])
+}
+
+pub fn play_card(hand_index: usize, target: Option<&Value>) -> Value {
+    json!([{ "HandCardUse": [hand_index, target] }])
+}
+
+// the hand card the inputs built by `play_card` use
+fn hand_index(inputs: &Value) -> Option<usize> {
+    inputs[0]["HandCardUse"][0]
+        .as_u64()
+        .map(|index| index as usize)
+}
+
+pub fn enemy_target(index: usize) -> Value {
+    json!({ "SingleEnemy": index })
+}
+
+// the core inputs behind one of the shapes above
+pub fn inputs(value: &Value) -> Result<Vec<IterationInput>, GameError> {
+    serde_json::from_value(value.clone()).map_err(GameError::invalid_selection("operations"))
+}
+
+fn selection(value: &Value) -> Result<Selection, GameError> {
+    serde_json::from_value(value.clone()).map_err(GameError::invalid_selection("selection"))
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ongoing,
+    Win,
+    Lose,
+}
+
+// steps reach the policies rendered, these read them back into core's own enums
+fn core_value<T: DeserializeOwned>(value: &Value, subject: &'static str) -> Result<T, GameError> {
+    serde_json::from_value(value.clone()).map_err(GameError::deserialize(subject))
+}
+
+pub fn outcome(output: &Value) -> Result<Outcome, GameError> {
+    Ok(match core_value(output, "battle output")? {
+        IterationOutput::Continue => Outcome::Ongoing,
+        IterationOutput::GameWin => Outcome::Win,
+        IterationOutput::GameLose => Outcome::Lose,
+    })
+}
+
+// the card a log entry reports the warrior played
+pub fn played_card(log: &Value) -> Result<Option<u16>, GameError> {
+    Ok(match core_value(log, "battle log")? {
+        FightLog::WarriorCardUse(card) => Some(card),
+        _ => None,
+    })
+}
+
+// how much a battle step hurt the enemies, minus how much it hurt the warrior, read from the
+// hp logs since core reports no health totals mid-battle
+pub fn score(step: &Value) -> Result<i64, GameError> {
+    match outcome(&step[0])? {
+        Outcome::Win => return Ok(i64::MAX / 2),
+        Outcome::Lose => return Ok(i64::MIN / 2),
+        Outcome::Ongoing => {}
+    }
+    let logs: Vec<FightLog> = core_value(&step[1], "battle logs")?;
+    Ok(logs
+        .iter()
+        .map(|log| match log {
+            FightLog::EnemyHpDecrease(_, amount) => *amount as i64,
+            FightLog::WarriorHpDecrease(amount) => -(*amount as i64),
+            _ => 0,
+        })
+        .sum())
+}
+
+// a scored try and, when it could be followed up, the fork it left behind
+type Attempt = (i64, Value, Option<Context>);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Policy {
+    // ends every turn without acting, the baseline for enemy pressure
+    Passive,
+    // plays random hand cards at random valid enemies
+    Random,
+    // plays whichever card scores best right now
+    #[default]
+    Greedy,
+    // like `Greedy`, but also weighs the best follow-up to each card
+    Lookahead,
+}
+
+impl Policy {
+    pub fn valid_targets(context: &Context) -> Result<Vec<Value>, GameError> {
+        let mut targets = vec![];
+        for index in 0..MAX_ENEMIES {
+            let target = enemy_target(index);
+            match context.peak_target(selection(&target)?) {
+                Ok(true) => targets.push(target),
+                Ok(false)
+                | Err(GameError::CoreError { .. } | GameError::InvalidSelection { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(targets)
+    }
+
+    // `Greedy` and `Lookahead` try inputs on forks of the game, and a fork plays out the very
+    // rng draws the real game makes next
+    pub fn forks(self) -> bool {
+        matches!(self, Policy::Greedy | Policy::Lookahead)
+    }
+
+    // ranked games would let a fork-based policy see the draws ahead of the leaderboard run
+    pub fn ensure_allowed(self, context: &Context) -> Result<(), GameError> {
+        if self.forks() && context.is_ranked() {
+            return Err(GameError::Ranked {
+                operation: "a policy that plays ahead on forks",
+            });
+        }
+        Ok(())
+    }
+
+    // plays `inputs` on a copy built from `fork` and scores the step, `None` when core refuses
+    // them; the copy is handed back when the battle goes on within the turn
+    fn attempt(fork: &Fork, inputs: &Value) -> Result<Option<(i64, Option<Context>)>, GameError> {
+        let fork = fork.build()?;
+        let step = match fork.iterate_battle(self::inputs(inputs)?, &JsonRenderer) {
+            Ok(step) => step,
+            Err(GameError::CoreError { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let goes_on = *inputs != end_turn() && outcome(&step[0])? == Outcome::Ongoing;
+        Ok(Some((score(&step)?, goes_on.then_some(fork))))
+    }
+
+    // every hand card at every valid target, then ending the turn, with their scores. each try
+    // replays the pending battle on a copy, so a card core refuses at the first target is not
+    // tried at the others
+    fn attempts(context: &Context, keep_forks: bool) -> Result<Vec<Attempt>, GameError> {
+        let fork = context.fork()?;
+        let targets = Self::valid_targets(context)?;
+        let targets: Vec<_> = if targets.is_empty() {
+            vec![None]
+        } else {
+            targets.iter().map(Some).collect()
+        };
+        let mut attempts = vec![];
+        for hand_index in 0..MAX_HAND {
+            for (nth, target) in targets.iter().enumerate() {
+                let inputs = play_card(hand_index, *target);
+                match Self::attempt(&fork, &inputs)? {
+                    Some((score, fork)) => {
+                        attempts.push((score, inputs, fork.filter(|_| keep_forks)))
+                    }
+                    None if nth == 0 => break,
+                    None => {}
+                }
+            }
+        }
+        let inputs = end_turn();
+        if let Some((score, _)) = Self::attempt(&fork, &inputs)? {
+            attempts.push((score, inputs, None));
+        }
+        Ok(attempts)
+    }
+
+    // ties go to the earlier attempt, so the choice never depends on sort internals
+    fn best(context: &Context, lookahead: bool) -> Result<Value, GameError> {
+        let mut attempts = Self::attempts(context, lookahead)?;
+        if lookahead {
+            attempts.sort_by_key(|(score, ..)| std::cmp::Reverse(*score));
+            attempts.truncate(LOOKAHEAD_BEAM);
+            for (score, _, fork) in &mut attempts {
+                if let Some(fork) = fork.take() {
+                    *score += Self::attempts(&fork, false)?
+                        .iter()
+                        .map(|(score, ..)| *score)
+                        .max()
+                        .unwrap_or_default();
+                }
+            }
+        }
+        let mut best: Option<(i64, Value)> = None;
+        for (score, inputs, _) in attempts {
+            if best.as_ref().is_none_or(|(best, _)| score > *best) {
+                best = Some((score, inputs));
+            }
+        }
+        Ok(best.map(|(_, inputs)| inputs).unwrap_or_else(end_turn))
+    }
+
+    // picks among the hand cards core has not refused this play, and ending the turn
+    fn random(context: &Context, refused: &[usize]) -> Result<Value, GameError> {
+        // seeded from the journal so a replayed auto battle picks the same cards
+        let (seed, actions) = context.journal_actions()?;
+        let mut rng = SmallRng::seed_from_u64(seed ^ actions.len() as u64 ^ refused.len() as u64);
+        let targets = Self::valid_targets(context)?;
+        let cards: Vec<_> = (0..MAX_HAND)
+            .filter(|index| !refused.contains(index))
+            .collect();
+        let pick = rng.gen_range(0..=cards.len());
+        if pick == cards.len() {
+            return Ok(end_turn());
+        }
+        let hand_index = cards[pick];
+        let target = (!targets.is_empty()).then(|| &targets[rng.gen_range(0..targets.len())]);
+        Ok(play_card(hand_index, target))
+    }
+
+    // the inputs this policy would send next, without sending them
+    pub fn next_inputs(self, context: &Context, plays: usize) -> Result<Value, GameError> {
+        self.choose(context, plays, &[])
+    }
+
+    fn choose(
+        self,
+        context: &Context,
+        plays: usize,
+        refused: &[usize],
+    ) -> Result<Value, GameError> {
+        self.ensure_allowed(context)?;
+        if plays >= MAX_PLAYS_PER_TURN {
+            return Ok(end_turn());
+        }
+        match self {
+            Policy::Passive => Ok(end_turn()),
+            Policy::Random => Self::random(context, refused),
+            Policy::Greedy => Self::best(context, false),
+            Policy::Lookahead => Self::best(context, true),
+        }
+    }
+
+    // plays a whole turn on a started battle and returns its last step
+    pub fn play_turn(
+        self,
+        context: &Context,
+        mut on_event: impl FnMut(BattleEvent),
+    ) -> Result<Value, GameError> {
+        let mut plays = 0;
+        // hand cards core refused since the last play, which leaves the hand as it was
+        let mut refused = vec![];
+        loop {
+            let inputs = self.choose(context, plays, &refused)?;
+            let ends_turn = inputs == end_turn();
+            let step = match context.iterate_battle_with(
+                self::inputs(&inputs)?,
+                &JsonRenderer,
+                &mut on_event,
+            ) {
+                Ok(step) => step,
+                // core refusing a card (no power, no card left): `Random` tries the others, the
+                // policies that tried it on a fork first have nothing else to play
+                Err(GameError::CoreError { .. }) if !ends_turn => {
+                    match hand_index(&inputs).filter(|_| self == Policy::Random) {
+                        Some(index) => refused.push(index),
+                        None => plays = MAX_PLAYS_PER_TURN,
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if ends_turn || outcome(&step[0])? != Outcome::Ongoing {
+                return Ok(step);
+            }
+            refused.clear();
+            plays += 1;
+        }
+    }
+}
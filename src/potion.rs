@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::inspect::warrior_ids;
+use crate::navigation::{starting_points, trial_session};
+
+// potions do not depend on the map, any seed gives the same reading
+const INSPECTION_SEED: u64 = 0;
+
+#[derive(Debug, Serialize)]
+pub struct PotionEffect {
+    pub player_id: u16,
+    pub warrior: Value,
+    // numeric warrior fields the potion changes, as the difference to the plain warrior
+    pub stat_modifiers: BTreeMap<String, i64>,
+    pub starting_cards: Vec<Value>,
+    pub added_cards: Vec<Value>,
+    pub removed_cards: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PotionReport {
+    // the potion as core decoded it
+    pub potion: Value,
+    // every warrior the potion can be used with
+    pub warriors: Vec<PotionEffect>,
+}
+
+fn rejected(reason: impl Into<String>) -> GameError {
+    GameError::InvalidSelection {
+        subject: "potion",
+        reason: reason.into(),
+    }
+}
+
+// the card list of a deck profile, which is either the list itself or its first list field
+pub(crate) fn cards(deck: &Value) -> Vec<Value> {
+    match deck {
+        Value::Array(cards) => cards.clone(),
+        Value::Object(fields) => fields
+            .values()
+            .find_map(Value::as_array)
+            .cloned()
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+// cards of `from` left once every card of `minus` took one match out
+fn difference(from: &[Value], minus: &[Value]) -> Vec<Value> {
+    let mut minus = minus.to_vec();
+    from.iter()
+        .filter(|card| match minus.iter().position(|v| v == *card) {
+            Some(index) => {
+                minus.swap_remove(index);
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+fn modifiers(plain: &Value, potion: &Value) -> BTreeMap<String, i64> {
+    let Some((plain, potion)) = plain.as_object().zip(potion.as_object()) else {
+        return BTreeMap::new();
+    };
+    potion
+        .iter()
+        .filter_map(|(stat, value)| {
+            let difference = value.as_i64()? - plain.get(stat)?.as_i64()?;
+            (difference != 0).then(|| (stat.clone(), difference))
+        })
+        .collect()
+}
+
+fn effect(
+    player_id: u16,
+    plain: &Context,
+    with_potion: &Context,
+) -> Result<PotionEffect, GameError> {
+    let warrior = with_potion.warrior_profile(&JsonRenderer)?;
+    let starting_cards = cards(&with_potion.deck_profile(&JsonRenderer)?);
+    let plain_cards = cards(&plain.deck_profile(&JsonRenderer)?);
+    Ok(PotionEffect {
+        player_id,
+        stat_modifiers: modifiers(&plain.warrior_profile(&JsonRenderer)?, &warrior),
+        added_cards: difference(&starting_cards, &plain_cards),
+        removed_cards: difference(&plain_cards, &starting_cards),
+        warrior,
+        starting_cards,
+    })
+}
+
+// starts throwaway sessions with and without the potion for every warrior of the pool and
+// reports what the potion changes, so a client can preview it before committing to a run
+pub fn inspect_potion(
+    raw_resource_pool: &[u8],
+    raw_potion: &[u8],
+) -> Result<PotionReport, GameError> {
+    if raw_potion.is_empty() {
+        return Err(rejected("the potion is empty"));
+    }
+    let map = Context::new(raw_resource_pool, INSPECTION_SEED)?.map_profile(&JsonRenderer)?;
+    let mut report = PotionReport {
+        potion: Value::Null,
+        warriors: vec![],
+    };
+    let mut reason = None;
+    for player_id in warrior_ids(raw_resource_pool)? {
+        // the first starting area the warrior can start from without a potion, so a refusal
+        // below is down to the potion alone
+        let plain = starting_points(&map, player_id)
+            .into_iter()
+            .find_map(|point| {
+                trial_session(raw_resource_pool, INSPECTION_SEED, player_id, point, &[])
+                    .ok()
+                    .map(|context| (point, context))
+            });
+        let Some((point, plain)) = plain else {
+            continue;
+        };
+        match trial_session(
+            raw_resource_pool,
+            INSPECTION_SEED,
+            player_id,
+            point,
+            raw_potion,
+        ) {
+            Ok(with_potion) => {
+                if let Some(potion) = with_potion.potion(&JsonRenderer)? {
+                    report.potion = potion;
+                }
+                report
+                    .warriors
+                    .push(effect(player_id, &plain, &with_potion)?);
+            }
+            Err(GameError::CoreError { reason: core, .. }) => reason = Some(core),
+            Err(e) => return Err(e),
+        }
+    }
+    if report.warriors.is_empty() {
+        return Err(rejected(match reason {
+            Some(reason) => format!("no warrior accepts it: {reason}"),
+            None => "the resource pool has no warrior that can start a session".to_owned(),
+        }));
+    }
+    Ok(report)
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::GameError;
+use crate::snapshot::{decode_with_header, encode_with_header, Action};
+
+pub const REPLAY_MAGIC: &[u8; 4] = b"SWRP";
+pub const REPLAY_VERSION: u16 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub action: Action,
+    pub digest: Option<[u8; 32]>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub resource_pool_hash: [u8; 32],
+    pub seed: u64,
+    pub player_id: Option<u16>,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    pub fn encode(&self) -> Result<Vec<u8>, GameError> {
+        encode_with_header("replay", REPLAY_MAGIC, REPLAY_VERSION, self)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, GameError> {
+        decode_with_header("replay", REPLAY_MAGIC, REPLAY_VERSION, bytes)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Divergence {
+    pub step: usize,
+    pub action: Action,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ReplayReport {
+    pub executed_steps: usize,
+    pub total_steps: usize,
+    pub divergence: Option<Divergence>,
+}
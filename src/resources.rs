@@ -0,0 +1,107 @@
+use serde::Serialize;
+use spore_warriors_resources::parse_to_binary;
+
+use crate::error::GameError;
+use crate::hash::blake2b_256;
+
+pub const RESOURCE_MAGIC: &[u8; 4] = b"SWRB";
+pub const RESOURCE_VERSION: u16 = 1;
+// magic, version, content hash and build timestamp ahead of the molecule content
+const HEADER_SIZE: usize = 4 + 2 + 32 + 8;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceHeader {
+    pub version: u16,
+    pub hash: [u8; 32],
+    // unix seconds, informational only
+    pub built_at: u64,
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |v| v.as_secs())
+}
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+fn now() -> u64 {
+    0
+}
+
+pub fn pack_resource_binary(content: &[u8], built_at: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + content.len());
+    bytes.extend(RESOURCE_MAGIC);
+    bytes.extend(RESOURCE_VERSION.to_le_bytes());
+    bytes.extend(blake2b_256(content));
+    bytes.extend(built_at.to_le_bytes());
+    bytes.extend(content);
+    bytes
+}
+
+// checks the container before anything reaches core, so stale or foreign binaries are
+// refused with a reason instead of failing somewhere inside `Game::new`
+pub fn unpack_resource_binary(bytes: &[u8]) -> Result<(ResourceHeader, &[u8]), GameError> {
+    let invalid = |reason: &str| GameError::InvalidPayload {
+        payload: "resource binary",
+        reason: reason.to_owned(),
+    };
+    if bytes.len() < HEADER_SIZE || &bytes[..4] != RESOURCE_MAGIC {
+        return Err(invalid(
+            "missing header, rebuild it with generate_resource_binary",
+        ));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != RESOURCE_VERSION {
+        return Err(GameError::UnsupportedVersion {
+            payload: "resource binary",
+            found: version,
+            expected: RESOURCE_VERSION,
+        });
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&bytes[6..38]);
+    let mut built_at = [0u8; 8];
+    built_at.copy_from_slice(&bytes[38..HEADER_SIZE]);
+    let content = &bytes[HEADER_SIZE..];
+    if blake2b_256(content) != hash {
+        return Err(invalid(
+            "content does not match its hash, the binary is truncated or corrupted",
+        ));
+    }
+    let header = ResourceHeader {
+        version,
+        hash,
+        built_at: u64::from_le_bytes(built_at),
+    };
+    Ok((header, content))
+}
+
+pub fn generate_resource_binary(
+    action_pool: &str,
+    card_pool: &str,
+    system_pool: &str,
+    enemy_pool: &str,
+    loot_pool: &str,
+    scene_pool: &str,
+    warrior_pool: &str,
+) -> Result<Vec<u8>, GameError> {
+    let content = parse_to_binary(
+        action_pool,
+        card_pool,
+        system_pool,
+        enemy_pool,
+        loot_pool,
+        scene_pool,
+        warrior_pool,
+    )
+    .map_err(|e| GameError::ResourcePool {
+        reason: e.to_string(),
+    })?;
+    Ok(pack_resource_binary(&content, now()))
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::hash::blake2b_256;
+
+const DOMAIN: &[u8] = b"spore-warriors-seed";
+
+// `map` is the seed the game itself is built from; core rolls loot while moving, on the same
+// controller, so it drives loot too. every battle draws from a controller of its own, seeded
+// from `battle` and the battle's index, so a fight never shifts the map's draws or those of
+// the fights after it. both depend on nothing but the master seed and the chosen warrior, so
+// two players of the same challenge get the same game however differently they play
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SeedInfo {
+    pub master: u64,
+    pub player_id: Option<u16>,
+    pub map: u64,
+    pub battle: u64,
+}
+
+fn stream(label: &str, parts: &[&[u8]]) -> u64 {
+    let mut input = DOMAIN.to_vec();
+    input.extend(label.as_bytes());
+    parts.iter().for_each(|part| input.extend(*part));
+    let hash = blake2b_256(&input);
+    u64::from_le_bytes(hash[..8].try_into().expect("hash is 32 bytes"))
+}
+
+impl SeedInfo {
+    // games created from a plain seed hand it to core as is
+    pub fn shared(seed: u64) -> Self {
+        Self {
+            master: seed,
+            player_id: None,
+            map: seed,
+            battle: Self::battle_seed(seed),
+        }
+    }
+
+    pub fn derive(master: u64, player_id: u16) -> Self {
+        let map = stream("map", &[&master.to_le_bytes(), &player_id.to_le_bytes()]);
+        Self {
+            master,
+            player_id: Some(player_id),
+            map,
+            battle: Self::battle_seed(map),
+        }
+    }
+
+    // follows from the map seed alone, so a journal replays its battles from its seed
+    pub fn battle_seed(map: u64) -> u64 {
+        stream("battle", &[&map.to_le_bytes()])
+    }
+
+    // the seed of the `index`th battle of a game
+    pub fn battle_stream(battle: u64, index: u64) -> u64 {
+        stream("battle", &[&battle.to_le_bytes(), &index.to_le_bytes()])
+    }
+}
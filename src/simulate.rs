@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::{Context, JsonRenderer};
+use crate::error::GameError;
+use crate::events::BattleEvent;
+use crate::policy::{self, Outcome, Policy};
+
+// fights still going after this many turns count as unfinished
+const MAX_TURNS: usize = 100;
+
+#[derive(Debug, Serialize, Default)]
+pub struct SimulationReport {
+    pub runs: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub unfinished: u32,
+    pub win_rate: f64,
+    pub average_turns: f64,
+    pub average_hp_remaining: f64,
+    pub card_usage: BTreeMap<u16, u32>,
+}
+
+// `hp` is only known once the battle is over and the warrior is back on the map
+pub(crate) struct Fight {
+    pub outcome: Outcome,
+    pub turns: usize,
+    pub hp: Option<u64>,
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(
+    value: &Value,
+    subject: &'static str,
+) -> Result<T, GameError> {
+    serde_json::from_value(value.clone()).map_err(GameError::deserialize(subject))
+}
+
+// plays the pending battle of `context` to its end and hands the warrior back to the map; a
+// battle still going after `MAX_TURNS` is left pending
+pub(crate) fn fight(
+    context: &Context,
+    policy: Policy,
+    mut on_event: impl FnMut(BattleEvent),
+) -> Result<Fight, GameError> {
+    let step = context.start_battle_with(&JsonRenderer, &mut on_event)?;
+    let mut outcome = policy::outcome(&step[0])?;
+    let mut turns = 0;
+    while outcome == Outcome::Ongoing && turns < MAX_TURNS {
+        outcome = policy::outcome(&policy.play_turn(context, &mut on_event)?[0])?;
+        turns += 1;
+    }
+    if outcome == Outcome::Ongoing {
+        return Ok(Fight {
+            outcome,
+            turns,
+            hp: None,
+        });
+    }
+    context.destroy_battle()?;
+    Ok(Fight {
+        outcome,
+        turns,
+        hp: Some(hp(context)?),
+    })
+}
+
+// the warrior's hp on the map, a profile without it is an error rather than a dead warrior
+pub(crate) fn hp(context: &Context) -> Result<u64, GameError> {
+    context.warrior_profile(&JsonRenderer)?["hp"]
+        .as_u64()
+        .ok_or_else(|| GameError::Deserialize {
+            subject: "warrior",
+            reason: "the profile has no numeric hp".to_owned(),
+        })
+}
+
+// run `n` plays on its own game seeded with `seed + n`, which also seeds `Policy::Random`,
+// so a report is reproducible
+pub fn simulate_battles(
+    raw_resource_pool: &[u8],
+    warrior: &Value,
+    deck: &Value,
+    enemies: &Value,
+    policy: Policy,
+    runs: u32,
+    seed: u64,
+) -> Result<SimulationReport, GameError> {
+    let mut report = SimulationReport {
+        runs,
+        ..Default::default()
+    };
+    let (mut turns, mut hp) = (0, 0);
+    for run in 0..runs {
+        let context = Context::new(raw_resource_pool, seed.wrapping_add(run as u64))?;
+        context.start_standalone_battle(
+            deserialize(warrior, "warrior")?,
+            deserialize(deck, "warrior_deck")?,
+            deserialize(enemies, "enemies")?,
+        )?;
+        let mut played = vec![];
+        let result = fight(&context, policy, |event| played.push(event.log))?;
+        for log in &played {
+            if let Some(card) = policy::played_card(log)? {
+                *report.card_usage.entry(card).or_default() += 1;
+            }
+        }
+        match result.outcome {
+            Outcome::Win => report.wins += 1,
+            Outcome::Lose => report.losses += 1,
+            Outcome::Ongoing => report.unfinished += 1,
+        }
+        turns += result.turns;
+        hp += result.hp.unwrap_or_default();
+    }
+    if runs > 0 {
+        report.win_rate = report.wins as f64 / runs as f64;
+        report.average_turns = turns as f64 / runs as f64;
+    }
+    // unfinished fights never handed the warrior back, so they have no hp to count
+    let finished = report.wins + report.losses;
+    if finished > 0 {
+        report.average_hp_remaining = hp as f64 / finished as f64;
+    }
+    Ok(report)
+}
@@ -0,0 +1,99 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::GameError;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"SWSS";
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+// every state-changing call on a game, replayed in order to rebuild it from the seed
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Action {
+    CreateSession {
+        player_id: u16,
+        point: (u8, u8),
+        potion: Vec<u8>,
+    },
+    MovePlayer {
+        point: (u8, u8),
+        selections: Vec<u8>,
+    },
+    StartBattle,
+    IterateBattle {
+        operations: serde_json::Value,
+    },
+    DestroyBattle,
+    EndSession,
+    // the json forms of what `create_standalone_battle` was given
+    StandaloneBattle {
+        warrior: serde_json::Value,
+        deck: serde_json::Value,
+        enemies: serde_json::Value,
+    },
+}
+
+// `digests` runs parallel to `actions` and only holds output hashes while `recording` is on
+#[derive(Default)]
+pub struct Journal {
+    pub seed: u64,
+    pub recording: bool,
+    pub actions: Vec<Action>,
+    pub digests: Vec<Option<[u8; 32]>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub resource_pool_hash: [u8; 32],
+    pub seed: u64,
+    pub actions: Vec<Action>,
+    pub warrior: Option<serde_json::Value>,
+    pub deck: Option<serde_json::Value>,
+}
+
+impl Snapshot {
+    pub fn encode(&self) -> Result<Vec<u8>, GameError> {
+        encode_with_header("snapshot", SNAPSHOT_MAGIC, SNAPSHOT_VERSION, self)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, GameError> {
+        decode_with_header("snapshot", SNAPSHOT_MAGIC, SNAPSHOT_VERSION, bytes)
+    }
+}
+
+pub fn encode_with_header<T: Serialize>(
+    payload: &'static str,
+    magic: &[u8; 4],
+    version: u16,
+    value: &T,
+) -> Result<Vec<u8>, GameError> {
+    let mut bytes = magic.to_vec();
+    bytes.extend(version.to_le_bytes());
+    bytes.extend(serde_json::to_vec(value).map_err(GameError::serialize(payload))?);
+    Ok(bytes)
+}
+
+pub fn decode_with_header<T: DeserializeOwned>(
+    payload: &'static str,
+    magic: &[u8; 4],
+    version: u16,
+    bytes: &[u8],
+) -> Result<T, GameError> {
+    if bytes.len() < 6 || &bytes[..4] != magic {
+        return Err(GameError::InvalidPayload {
+            payload,
+            reason: "missing header".to_owned(),
+        });
+    }
+    let found = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if found != version {
+        return Err(GameError::UnsupportedVersion {
+            payload,
+            found,
+            expected: version,
+        });
+    }
+    serde_json::from_slice(&bytes[6..]).map_err(|e| GameError::InvalidPayload {
+        payload,
+        reason: e.to_string(),
+    })
+}
@@ -0,0 +1,315 @@
+use wasm_bindgen::prelude::*;
+
+// shapes produced by this crate are spelled out field by field; payloads owned by
+// spore-warriors-core follow its `json_serde` layout, with every field this crate reads or
+// builds spelled out and the rest left open
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &str = r#"
+export interface Point {
+    x: number;
+    y: number;
+}
+
+export interface MapNode {
+    point: Point;
+    [field: string]: unknown;
+}
+
+export interface MapProfile {
+    width: number;
+    height: number;
+    [field: string]: unknown;
+}
+
+export interface Potion {
+    [field: string]: unknown;
+}
+
+export interface WarriorProfile {
+    hp: number;
+    name?: string;
+    [field: string]: unknown;
+}
+
+export interface Card {
+    [field: string]: unknown;
+}
+
+// either the card list itself or a record whose first list field holds the cards
+export type WarriorDeckProfile = Card[] | { [field: string]: Card[] | unknown };
+
+export interface Enemy {
+    [field: string]: unknown;
+}
+
+// externally tagged enums: unit variants are plain strings, the rest one-key objects
+export type MoveResult = { Fight: unknown } | string | { [variant: string]: unknown };
+export type BattleOutput = string | { [variant: string]: unknown };
+export type BattleLog = string | { [variant: string]: unknown[] | unknown };
+export type Selection = { SingleEnemy: number } | { [variant: string]: number | number[] };
+export type IterationInput = "EndTurn" | { HandCardUse: [number, Selection | null] };
+
+export type BattleStep = [BattleOutput, BattleLog[]];
+
+export type BattleActor = "Warrior" | "Unknown" | { Enemy: number | null };
+
+export interface BattleEvent {
+    turn: number;
+    sequence: number;
+    kind: string;
+    actor: BattleActor;
+    log: BattleLog;
+}
+
+export type Policy = "Passive" | "Random" | "Greedy" | "Lookahead";
+
+export interface SimulationReport {
+    runs: number;
+    wins: number;
+    losses: number;
+    unfinished: number;
+    win_rate: number;
+    average_turns: number;
+    average_hp_remaining: number;
+    card_usage: { [card: string]: number };
+}
+
+export interface ReachablePoint {
+    point: [number, number];
+    node: MapNode;
+}
+
+export interface VisibleMap {
+    width: number;
+    height: number;
+    nodes: { point: [number, number]; node: MapNode }[];
+}
+
+export interface Visibility {
+    visited: [number, number][];
+    revealed: [number, number][];
+}
+
+export type RunOutcome = "Died" | "Completed" | "Stuck" | "Unfinished" | "StepLimit";
+
+export interface RunSummary {
+    outcome: RunOutcome;
+    path: [number, number][];
+    battles_won: number;
+    battles_lost: number;
+    battle_turns: number;
+    warrior: WarriorProfile | null;
+}
+
+export type Pool = "action" | "card" | "system" | "enemy" | "loot" | "scene" | "warrior";
+
+export type DiagnosticKind =
+    | "Unparsable"
+    | "MissingId"
+    | "DuplicateId"
+    | "DanglingReference"
+    | "EnemyWithoutActions"
+    | "NoStartingArea"
+    | "Rejected";
+
+export interface Diagnostic {
+    kind: DiagnosticKind;
+    pool: Pool | null;
+    id: number | null;
+    message: string;
+}
+
+export type PoolField = number | string | PoolField[];
+
+export interface PoolEntry {
+    id: number;
+    fields: PoolField[];
+}
+
+export interface PoolSummary {
+    pool: Pool;
+    count: number;
+    ids: number[];
+    entries: PoolEntry[];
+}
+
+export interface ResourceHeader {
+    version: number;
+    hash: number[];
+    built_at: number;
+}
+
+export interface ResourceInspection {
+    header: ResourceHeader;
+    size: number;
+    pools: PoolSummary[];
+}
+
+export type EntryChange = "Added" | "Removed" | "Changed";
+
+export interface EntryDiff {
+    pool: Pool;
+    id: number;
+    change: EntryChange;
+    before: PoolEntry | null;
+    after: PoolEntry | null;
+}
+
+export interface SeedInfo {
+    master: bigint;
+    player_id: number | null;
+    map: bigint;
+    battle: bigint;
+}
+
+export interface DailyChallenge {
+    date: string;
+    master_seed: bigint;
+    player_id: number;
+    point: [number, number];
+}
+
+export interface RunDigest {
+    date: string;
+    master_seed: bigint;
+    player_id: number;
+    steps: bigint;
+    warrior: WarriorProfile | null;
+    digest: number[];
+}
+
+export interface PotionEffect {
+    player_id: number;
+    warrior: WarriorProfile;
+    stat_modifiers: { [stat: string]: number };
+    starting_cards: Card[];
+    added_cards: Card[];
+    removed_cards: Card[];
+}
+
+export interface PotionReport {
+    potion: Potion | null;
+    warriors: PotionEffect[];
+}
+
+export interface WarriorEntry {
+    player_id: number;
+    name: string | null;
+    stats: { [stat: string]: number };
+    deck: Card[];
+    starting_points: [number, number][];
+}
+
+export type Action =
+    | { CreateSession: { player_id: number; point: [number, number]; potion: number[] } }
+    | { MovePlayer: { point: [number, number]; selections: number[] } }
+    | "StartBattle"
+    | { IterateBattle: { operations: IterationInput[] } }
+    | "DestroyBattle"
+    | "EndSession"
+    | { StandaloneBattle: { warrior: WarriorProfile; deck: WarriorDeckProfile; enemies: Enemy[] } };
+
+export interface Divergence {
+    step: number;
+    action: Action;
+    reason: string;
+}
+
+export interface ReplayReport {
+    executed_steps: number;
+    total_steps: number;
+    divergence: Divergence | null;
+}
+
+export type SubsystemHealth = "Ready" | "Empty" | "Busy" | "Poisoned";
+
+export interface Health {
+    game: SubsystemHealth;
+    warrior: SubsystemHealth;
+    deck: SubsystemHealth;
+    battle: SubsystemHealth;
+    journal: SubsystemHealth;
+    consistent: boolean;
+}
+
+export type GameError = { message: string } & (
+    | { kind: "NotInitialized"; subject: string }
+    | { kind: "AlreadyInitialized"; subject: string }
+    | { kind: "BattleNotTriggered" }
+    | { kind: "BattleAlreadyTriggered" }
+    | { kind: "InvalidSelection"; subject: string; reason: string }
+    | { kind: "CoreError"; code: string; reason: string }
+    | { kind: "Deserialize"; subject: string; reason: string }
+    | { kind: "Serialize"; subject: string; reason: string }
+    | { kind: "InvalidPayload"; payload: string; reason: string }
+    | { kind: "UnsupportedVersion"; payload: string; found: number; expected: number }
+    | { kind: "ResourcePoolMismatch"; payload: string }
+    | { kind: "Diverged"; payload: string }
+    | { kind: "ResourcePool"; reason: string }
+    | { kind: "Poisoned"; reason: string }
+    | { kind: "Reentrant" }
+    | { kind: "Panicked"; reason: string }
+    | { kind: "ResetIncomplete"; reasons: string[] }
+    | { kind: "Ranked"; operation: string }
+    | { kind: "NothingToUndo" }
+    | { kind: "NothingToRedo" }
+);
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "MapProfile")]
+    pub type MapProfile;
+    #[wasm_bindgen(typescript_type = "MapNode | null")]
+    pub type OptionalMapNode;
+    #[wasm_bindgen(typescript_type = "Potion | null")]
+    pub type OptionalPotion;
+    #[wasm_bindgen(typescript_type = "WarriorProfile")]
+    pub type WarriorProfile;
+    #[wasm_bindgen(typescript_type = "WarriorDeckProfile")]
+    pub type WarriorDeckProfile;
+    #[wasm_bindgen(typescript_type = "Enemy[]")]
+    pub type EnemyList;
+    #[wasm_bindgen(typescript_type = "MoveResult")]
+    pub type MoveResult;
+    #[wasm_bindgen(typescript_type = "BattleStep")]
+    pub type BattleStep;
+    #[wasm_bindgen(typescript_type = "(event: BattleEvent) => void")]
+    pub type BattleEventListener;
+    #[wasm_bindgen(typescript_type = "IterationInput[]")]
+    pub type IterationInputList;
+    #[wasm_bindgen(typescript_type = "Selection")]
+    pub type Selection;
+    #[wasm_bindgen(typescript_type = "Policy")]
+    pub type Policy;
+    #[wasm_bindgen(typescript_type = "SimulationReport")]
+    pub type SimulationReport;
+    #[wasm_bindgen(typescript_type = "ReachablePoint[]")]
+    pub type ReachablePointList;
+    #[wasm_bindgen(typescript_type = "VisibleMap")]
+    pub type VisibleMap;
+    #[wasm_bindgen(typescript_type = "Visibility")]
+    pub type Visibility;
+    #[wasm_bindgen(typescript_type = "RunSummary")]
+    pub type RunSummary;
+    #[wasm_bindgen(typescript_type = "Diagnostic[]")]
+    pub type DiagnosticList;
+    #[wasm_bindgen(typescript_type = "ResourceInspection")]
+    pub type ResourceInspection;
+    #[wasm_bindgen(typescript_type = "EntryDiff[]")]
+    pub type EntryDiffList;
+    #[wasm_bindgen(typescript_type = "SeedInfo")]
+    pub type SeedInfo;
+    #[wasm_bindgen(typescript_type = "DailyChallenge | null")]
+    pub type OptionalDailyChallenge;
+    #[wasm_bindgen(typescript_type = "RunDigest")]
+    pub type RunDigest;
+    #[wasm_bindgen(typescript_type = "PotionReport")]
+    pub type PotionReport;
+    #[wasm_bindgen(typescript_type = "WarriorEntry[]")]
+    pub type WarriorEntryList;
+    #[wasm_bindgen(typescript_type = "ReplayReport")]
+    pub type ReplayReport;
+    #[wasm_bindgen(typescript_type = "Health")]
+    pub type Health;
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context::Context;
+use crate::error::GameError;
+use crate::navigation::map_layout;
+use crate::snapshot::Action;
+
+// `visited` is read off the journal and `revealed` is kept with the game, which undo,
+// recover and snapshots rebuild from the journal, so neither gets out of step
+#[derive(Debug, Serialize, Default)]
+pub struct Visibility {
+    pub visited: BTreeSet<(u8, u8)>,
+    pub revealed: BTreeSet<(u8, u8)>,
+}
+
+// the map as far as the warrior has seen it: its size and the nodes at revealed points
+#[derive(Debug, Serialize)]
+pub struct VisibleMap {
+    pub width: u16,
+    pub height: u16,
+    pub nodes: Vec<VisibleNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VisibleNode {
+    pub point: (u8, u8),
+    pub node: Value,
+}
+
+fn visited(actions: &[Action]) -> BTreeSet<(u8, u8)> {
+    let mut visited = BTreeSet::new();
+    for action in actions {
+        match action {
+            Action::CreateSession { point, .. } => {
+                visited.clear();
+                visited.insert(*point);
+            }
+            Action::MovePlayer { point, .. } => {
+                visited.insert(*point);
+            }
+            Action::EndSession => visited.clear(),
+            _ => {}
+        }
+    }
+    visited
+}
+
+// a point is revealed once visited or once core previewed a move to it from anywhere the
+// warrior stood, so no sight range has to be read out of the warrior profile
+pub fn visibility(context: &Context) -> Result<Visibility, GameError> {
+    let (_, actions) = context.journal_actions()?;
+    let visited = visited(&actions);
+    if visited.is_empty() {
+        return Ok(Visibility::default());
+    }
+    let mut revealed = visited.clone();
+    revealed.extend(context.revealed()?);
+    Ok(Visibility { visited, revealed })
+}
+
+// built up from the revealed points alone, so a node laid out in a way this crate does not
+// recognise stays hidden rather than leaking
+pub fn visible_profile(context: &Context) -> Result<VisibleMap, GameError> {
+    let revealed = visibility(context)?.revealed;
+    let layout = map_layout(context)?;
+    let nodes = layout
+        .nodes
+        .into_iter()
+        .filter(|(point, _)| revealed.contains(point))
+        .map(|(point, node)| VisibleNode { point, node })
+        .collect();
+    Ok(VisibleMap {
+        width: layout.columns,
+        height: layout.rows,
+        nodes,
+    })
+}
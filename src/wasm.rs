@@ -0,0 +1,553 @@
+use std::cell::RefCell;
+use std::panic;
+use std::sync::{Arc, Once};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use spore_warriors_core::contexts::{WarriorContext, WarriorDeckContext};
+use spore_warriors_core::wrappings::Enemy;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::context::{self, Context, Renderer};
+use crate::error::GameError;
+use crate::events::BattleEvent;
+use crate::policy::Policy;
+use crate::{
+    autopilot, catalog, daily, inspect, lint, navigation, potion, resources, simulate, types,
+    visibility,
+};
+
+// plain objects rather than js `Map`s, so payloads look the same as their json form
+const SERIALIZER: serde_wasm_bindgen::Serializer =
+    serde_wasm_bindgen::Serializer::json_compatible();
+
+struct JsRenderer;
+
+impl Renderer for JsRenderer {
+    type Output = JsValue;
+
+    fn render<V: Serialize + ?Sized>(&self, value: &V) -> Result<Self::Output, GameError> {
+        value
+            .serialize(&SERIALIZER)
+            .map_err(GameError::serialize("output"))
+    }
+}
+
+fn to_ts<T: JsCast, V: Serialize + ?Sized>(value: &V) -> Result<T, GameError> {
+    JsRenderer.render(value).map(JsCast::unchecked_into)
+}
+
+// for payloads carrying seeds, which use the whole u64 range and go out as bigints like
+// they came in
+fn to_ts_wide<T: JsCast, V: Serialize + ?Sized>(
+    value: &V,
+    subject: &'static str,
+) -> Result<T, GameError> {
+    value
+        .serialize(&SERIALIZER.serialize_large_number_types_as_bigints(true))
+        .map(JsCast::unchecked_into)
+        .map_err(GameError::serialize(subject))
+}
+
+fn or_null<T: JsCast>(value: Option<JsValue>) -> T {
+    value.unwrap_or(JsValue::NULL).unchecked_into()
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue, subject: &'static str) -> Result<T, GameError> {
+    serde_wasm_bindgen::from_value(value).map_err(GameError::deserialize(subject))
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    pub fn log(s: &str);
+    #[wasm_bindgen(js_namespace = console)]
+    pub fn error(s: &str);
+}
+
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            error(&info.to_string());
+            default_hook(info);
+        }));
+    });
+}
+
+#[wasm_bindgen]
+pub struct WasmGame {
+    context: Arc<Context>,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    pub fn get_potion(&self) -> Result<types::OptionalPotion, GameError> {
+        self.context.potion(&JsRenderer).map(or_null)
+    }
+
+    pub fn get_map(&self) -> WasmMap {
+        WasmMap {
+            context: self.context.clone(),
+        }
+    }
+
+    pub fn create_session(
+        &self,
+        player_id: u16,
+        point_x: u8,
+        point_y: u8,
+        raw_potion: &[u8],
+    ) -> Result<(), GameError> {
+        self.context.guarded(|| {
+            self.context
+                .create_session(player_id, point_x, point_y, raw_potion)
+        })
+    }
+
+    pub fn end_session(&self) -> Result<(), GameError> {
+        self.context.end_session()
+    }
+
+    pub fn list_warriors(&self) -> Result<types::WarriorEntryList, GameError> {
+        to_ts(&catalog::list_warriors(&self.context)?)
+    }
+
+    pub fn get_seed_info(&self) -> Result<types::SeedInfo, GameError> {
+        to_ts_wide(&self.context.seed_info(), "seed info")
+    }
+
+    pub fn get_daily_challenge(&self) -> Result<types::OptionalDailyChallenge, GameError> {
+        to_ts_wide(&self.context.daily(), "daily challenge")
+    }
+
+    // submitted to the leaderboard along with `export_replay`
+    pub fn get_run_digest(&self) -> Result<types::RunDigest, GameError> {
+        to_ts_wide(&daily::run_digest(&self.context)?, "run digest")
+    }
+
+    pub fn export_snapshot(&self) -> Result<Vec<u8>, GameError> {
+        self.context.export_snapshot()
+    }
+
+    pub fn import_snapshot(&self, bytes: &[u8]) -> Result<(), GameError> {
+        self.context.import_snapshot(bytes)
+    }
+
+    pub fn set_replay_recording(&self, enabled: bool) -> Result<(), GameError> {
+        self.context.set_replay_recording(enabled)
+    }
+
+    pub fn export_replay(&self) -> Result<Vec<u8>, GameError> {
+        self.context.export_replay()
+    }
+
+    pub fn set_history_capacity(&self, capacity: usize) -> Result<(), GameError> {
+        self.context.set_history_capacity(capacity)
+    }
+
+    pub fn is_ranked(&self) -> bool {
+        self.context.is_ranked()
+    }
+
+    pub fn health(&self) -> Result<types::Health, GameError> {
+        to_ts(&self.context.health())
+    }
+
+    pub fn recover(&self) -> Result<(), GameError> {
+        self.context.recover()
+    }
+
+    pub fn destroy(self) -> Result<(), GameError> {
+        self.context.release()
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmMap {
+    context: Arc<Context>,
+}
+
+#[wasm_bindgen]
+impl WasmMap {
+    // the whole map, hidden nodes included, is for debug builds only
+    #[cfg(debug_assertions)]
+    pub fn get_profile(&self) -> Result<types::MapProfile, GameError> {
+        if self.context.is_ranked() {
+            return Err(GameError::Ranked {
+                operation: "the full map profile",
+            });
+        }
+        self.context
+            .map_profile(&JsRenderer)
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn get_visible_profile(&self) -> Result<types::VisibleMap, GameError> {
+        to_ts(&visibility::visible_profile(&self.context)?)
+    }
+
+    pub fn get_visibility(&self) -> Result<types::Visibility, GameError> {
+        to_ts(&visibility::visibility(&self.context)?)
+    }
+
+    pub fn get_warrior_profile(&self) -> Result<types::WarriorProfile, GameError> {
+        self.context
+            .warrior_profile(&JsRenderer)
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn get_warrior_deck_profile(&self) -> Result<types::WarriorDeckProfile, GameError> {
+        self.context
+            .deck_profile(&JsRenderer)
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn peak_movement(
+        &self,
+        point_x: u8,
+        point_y: u8,
+    ) -> Result<types::OptionalMapNode, GameError> {
+        self.context
+            .peak_movement(point_x, point_y, &JsRenderer)
+            .map(or_null)
+    }
+
+    pub fn move_player(
+        &self,
+        point_x: u8,
+        point_y: u8,
+        selections: Vec<u8>,
+    ) -> Result<types::MoveResult, GameError> {
+        self.context
+            .guarded(|| {
+                self.context
+                    .move_player(point_x, point_y, selections, &JsRenderer)
+            })
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn undo(&self) -> Result<(), GameError> {
+        self.context.guarded(|| self.context.undo())
+    }
+
+    pub fn redo(&self) -> Result<(), GameError> {
+        self.context.guarded(|| self.context.redo())
+    }
+
+    pub fn can_undo(&self) -> Result<bool, GameError> {
+        self.context.can_undo()
+    }
+
+    pub fn can_redo(&self) -> Result<bool, GameError> {
+        self.context.can_redo()
+    }
+
+    pub fn reachable_points(&self) -> Result<types::ReachablePointList, GameError> {
+        to_ts(&navigation::reachable_points(&self.context)?)
+    }
+
+    pub fn autopilot(
+        &self,
+        policy: types::Policy,
+        max_steps: Option<usize>,
+    ) -> Result<types::RunSummary, GameError> {
+        let policy: Policy = from_js(policy.into(), "policy")?;
+        let max_steps = max_steps.unwrap_or(autopilot::MAX_STEPS);
+        let summary = self
+            .context
+            .guarded(|| autopilot::autopilot(&self.context, policy, max_steps))?;
+        to_ts(&summary)
+    }
+
+    pub fn create_pve_battle(&self) -> Result<WasmBattle, GameError> {
+        if !self.context.has_battle()? {
+            return Err(GameError::BattleNotTriggered);
+        }
+        Ok(WasmBattle::new(self.context.clone()))
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmBattle {
+    context: Arc<Context>,
+    listener: RefCell<Option<js_sys::Function>>,
+}
+
+impl WasmBattle {
+    fn new(context: Arc<Context>) -> Self {
+        WasmBattle {
+            context,
+            listener: RefCell::new(None),
+        }
+    }
+
+    // the listener is cloned up front so it may replace itself while being called
+    fn dispatch(&self) -> impl FnMut(BattleEvent) {
+        let listener = self.listener.borrow().clone();
+        move |event| {
+            let Some(listener) = &listener else {
+                return;
+            };
+            let result = JsRenderer
+                .render(&event)
+                .map_err(JsValue::from)
+                .and_then(|event| listener.call1(&JsValue::NULL, &event));
+            if let Err(e) = result {
+                error(&format!("battle event listener failed: {e:?}"));
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl WasmBattle {
+    // the listener gets every log of `start` and `iterate` one by one, in order, once core
+    // returns the step, before the call itself returns
+    pub fn subscribe(&self, listener: types::BattleEventListener) {
+        *self.listener.borrow_mut() = Some(listener.unchecked_into());
+    }
+
+    pub fn unsubscribe(&self) {
+        *self.listener.borrow_mut() = None;
+    }
+
+    pub fn start(&self) -> Result<types::BattleStep, GameError> {
+        self.context
+            .guarded(|| self.context.start_battle_with(&JsRenderer, self.dispatch()))
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn iterate(
+        &self,
+        input: types::IterationInputList,
+    ) -> Result<types::BattleStep, GameError> {
+        let operations = serde_wasm_bindgen::from_value(input.into())
+            .map_err(GameError::invalid_selection("operations"))?;
+        self.context
+            .guarded(|| {
+                self.context
+                    .iterate_battle_with(operations, &JsRenderer, self.dispatch())
+            })
+            .map(JsCast::unchecked_into)
+    }
+
+    pub fn check_peak_target(&self, selection: types::Selection) -> Result<bool, GameError> {
+        let selection = serde_wasm_bindgen::from_value(selection.into())
+            .map_err(GameError::invalid_selection("selection"))?;
+        self.context.peak_target(selection)
+    }
+
+    pub fn suggest_move(
+        &self,
+        policy: types::Policy,
+    ) -> Result<types::IterationInputList, GameError> {
+        let policy: Policy = from_js(policy.into(), "policy")?;
+        let inputs = self
+            .context
+            .guarded(|| policy.next_inputs(&self.context, 0))?;
+        to_ts(&inputs)
+    }
+
+    pub fn auto_play_turn(&self, policy: types::Policy) -> Result<types::BattleStep, GameError> {
+        let policy: Policy = from_js(policy.into(), "policy")?;
+        let step = self
+            .context
+            .guarded(|| policy.play_turn(&self.context, self.dispatch()))?;
+        to_ts(&step)
+    }
+
+    pub fn undo(&self) -> Result<(), GameError> {
+        self.context.guarded(|| self.context.undo())
+    }
+
+    pub fn redo(&self) -> Result<(), GameError> {
+        self.context.guarded(|| self.context.redo())
+    }
+
+    pub fn destroy(self) -> Result<(), GameError> {
+        self.context.guarded(|| self.context.destroy_battle())
+    }
+}
+
+#[wasm_bindgen]
+pub fn create_game(raw_resource_pool: &[u8], seed: u64) -> Result<WasmGame, GameError> {
+    install_panic_hook();
+    Ok(WasmGame {
+        context: Context::create(raw_resource_pool, seed)?,
+    })
+}
+
+#[wasm_bindgen]
+pub fn create_seeded_game(
+    raw_resource_pool: &[u8],
+    master_seed: u64,
+    player_id: u16,
+) -> Result<WasmGame, GameError> {
+    install_panic_hook();
+    Ok(WasmGame {
+        context: Context::create_seeded(raw_resource_pool, master_seed, player_id)?,
+    })
+}
+
+#[wasm_bindgen]
+pub fn create_daily_game(raw_resource_pool: &[u8], date: &str) -> Result<WasmGame, GameError> {
+    install_panic_hook();
+    Ok(WasmGame {
+        context: daily::create_daily_game(raw_resource_pool, date)?,
+    })
+}
+
+#[wasm_bindgen]
+pub fn verify_daily_run(
+    raw_resource_pool: &[u8],
+    date: &str,
+    replay: &[u8],
+) -> Result<types::RunDigest, GameError> {
+    to_ts_wide(
+        &daily::verify_daily_run(raw_resource_pool, date, replay)?,
+        "run digest",
+    )
+}
+
+#[wasm_bindgen]
+pub fn create_ranked_game(raw_resource_pool: &[u8], seed: u64) -> Result<WasmGame, GameError> {
+    install_panic_hook();
+    Ok(WasmGame {
+        context: Context::create_ranked(raw_resource_pool, seed)?,
+    })
+}
+
+#[wasm_bindgen]
+pub fn create_standalone_battle(
+    game: &WasmGame,
+    warrior: types::WarriorProfile,
+    warrior_deck: types::WarriorDeckProfile,
+    enemies: types::EnemyList,
+) -> Result<WasmBattle, GameError> {
+    let player: WarriorContext = from_js(warrior.into(), "warrior")?;
+    let player_deck: WarriorDeckContext = from_js(warrior_deck.into(), "warrior_deck")?;
+    let enemies: Vec<Enemy> = from_js(enemies.into(), "enemies")?;
+    let context = game
+        .context
+        .create_standalone_battle(player, player_deck, enemies)?;
+    Ok(WasmBattle::new(context))
+}
+
+#[wasm_bindgen]
+pub fn simulate_battles(
+    game: &WasmGame,
+    warrior: types::WarriorProfile,
+    warrior_deck: types::WarriorDeckProfile,
+    enemies: types::EnemyList,
+    policy: types::Policy,
+    runs: u32,
+    seed: u64,
+) -> Result<types::SimulationReport, GameError> {
+    let report = simulate::simulate_battles(
+        game.context.resource_pool(),
+        &from_js(warrior.into(), "warrior")?,
+        &from_js(warrior_deck.into(), "warrior_deck")?,
+        &from_js(enemies.into(), "enemies")?,
+        from_js(policy.into(), "policy")?,
+        runs,
+        seed,
+    )?;
+    to_ts(&report)
+}
+
+#[wasm_bindgen]
+pub fn smoke_test_run(
+    raw_resource_pool: &[u8],
+    seed: u64,
+    player_id: u16,
+    point_x: u8,
+    point_y: u8,
+    policy: types::Policy,
+) -> Result<types::RunSummary, GameError> {
+    let policy = from_js(policy.into(), "policy")?;
+    let summary = autopilot::smoke_test_run(
+        raw_resource_pool,
+        seed,
+        player_id,
+        (point_x, point_y),
+        policy,
+    )?;
+    to_ts(&summary)
+}
+
+#[wasm_bindgen]
+pub fn replay(raw_resource_pool: &[u8], bytes: &[u8]) -> Result<types::ReplayReport, GameError> {
+    to_ts(&context::verify_replay(raw_resource_pool, bytes)?)
+}
+
+#[wasm_bindgen]
+pub fn reset_all() -> Result<(), GameError> {
+    context::reset_all()
+}
+
+#[wasm_bindgen]
+pub fn generate_resource_binary(
+    action_pool: String,
+    card_pool: String,
+    system_pool: String,
+    enemy_pool: String,
+    loot_pool: String,
+    scene_pool: String,
+    warrior_pool: String,
+) -> Result<Vec<u8>, GameError> {
+    resources::generate_resource_binary(
+        &action_pool,
+        &card_pool,
+        &system_pool,
+        &enemy_pool,
+        &loot_pool,
+        &scene_pool,
+        &warrior_pool,
+    )
+}
+
+#[wasm_bindgen]
+pub fn inspect_potion(
+    raw_resource_pool: &[u8],
+    raw_potion: &[u8],
+) -> Result<types::PotionReport, GameError> {
+    to_ts(&potion::inspect_potion(raw_resource_pool, raw_potion)?)
+}
+
+#[wasm_bindgen]
+pub fn inspect_resource_binary(bytes: &[u8]) -> Result<types::ResourceInspection, GameError> {
+    to_ts(&inspect::inspect_resource_binary(bytes)?)
+}
+
+#[wasm_bindgen]
+pub fn diff_resource_binaries(
+    before: &[u8],
+    after: &[u8],
+) -> Result<types::EntryDiffList, GameError> {
+    to_ts(&inspect::diff_resource_binaries(before, after)?)
+}
+
+#[wasm_bindgen]
+pub fn validate_resource_pools(
+    action_pool: String,
+    card_pool: String,
+    system_pool: String,
+    enemy_pool: String,
+    loot_pool: String,
+    scene_pool: String,
+    warrior_pool: String,
+) -> Result<types::DiagnosticList, GameError> {
+    let diagnostics = lint::validate_resource_pools(
+        &action_pool,
+        &card_pool,
+        &system_pool,
+        &enemy_pool,
+        &loot_pool,
+        &scene_pool,
+        &warrior_pool,
+    );
+    to_ts(&diagnostics)
+}
@@ -0,0 +1,42 @@
+#![allow(dead_code)]
+
+use serde_json::Value;
+use spore_warriors_wasm::generate_resource_binary;
+
+pub const SEED: u64 = 20240401;
+pub const PLAYER_ID: u16 = 1;
+pub const START: (u8, u8) = (0, 0);
+// the one enemy node of the fixture scene, one step away from `START`; the scene also has a
+// starting area at `START` and an empty targeting area in its far corner
+pub const FIGHT: (u8, u8) = (1, 1);
+pub const END_TURN: &str = r#"["EndTurn"]"#;
+// a card the fixture hand never holds this many of
+pub const UNKNOWN_CARD: &str = r#"[{ "HandCardUse": [99, null] }]"#;
+pub const FIRST_ENEMY: &str = r#"{ "SingleEnemy": 0 }"#;
+
+// json sources in the argument order of `generate_resource_binary`
+pub const POOLS: [&str; 7] = [
+    include_str!("../fixtures/action_pool.json"),
+    include_str!("../fixtures/card_pool.json"),
+    include_str!("../fixtures/system_pool.json"),
+    include_str!("../fixtures/enemy_pool.json"),
+    include_str!("../fixtures/loot_pool.json"),
+    include_str!("../fixtures/scene_pool.json"),
+    include_str!("../fixtures/warrior_pool.json"),
+];
+
+pub fn fixture_pool() -> Vec<u8> {
+    let [action, card, system, enemy, loot, scene, warrior] = POOLS;
+    generate_resource_binary(action, card, system, enemy, loot, scene, warrior)
+        .expect("fixture resource pool")
+}
+
+// `pool` with `entries` appended to its list
+pub fn with_entries(pool: &str, entries: Value) -> String {
+    let mut pool: Value = serde_json::from_str(pool).expect("fixture pool");
+    pool.as_object_mut()
+        .and_then(|v| v.values_mut().find_map(Value::as_array_mut))
+        .expect("fixture pool list")
+        .extend(entries.as_array().cloned().unwrap_or_default());
+    pool.to_string()
+}
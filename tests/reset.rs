@@ -0,0 +1,23 @@
+mod common;
+
+use common::{fixture_pool, PLAYER_ID, SEED, START};
+use spore_warriors_wasm::health::SubsystemHealth;
+use spore_warriors_wasm::{reset_all, Context};
+
+// kept out of `session.rs` since `reset_all` would release games of tests running alongside
+#[test]
+fn reset_releases_every_game() {
+    let first = Context::create(&fixture_pool(), SEED).unwrap();
+    first
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+    let second = Context::create(&fixture_pool(), SEED).unwrap();
+
+    reset_all().unwrap();
+    for context in [first, second] {
+        let health = context.health();
+        assert_eq!(health.game, SubsystemHealth::Empty);
+        assert_eq!(health.warrior, SubsystemHealth::Empty);
+        assert!(health.consistent);
+    }
+}
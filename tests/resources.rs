@@ -0,0 +1,207 @@
+mod common;
+
+use common::{fixture_pool, with_entries, PLAYER_ID, POOLS, SEED, START};
+use serde_json::json;
+use spore_warriors_wasm::inspect::EntryChange;
+use spore_warriors_wasm::lint::DiagnosticKind;
+use spore_warriors_wasm::resources::{pack_resource_binary, unpack_resource_binary};
+use spore_warriors_wasm::{
+    diff_resource_binaries, generate_resource_binary, inspect_potion, inspect_resource_binary,
+    validate_resource_pools, Context, Diagnostic, GameError,
+};
+
+fn validate(pools: [&str; 7]) -> Vec<Diagnostic> {
+    let [action, card, system, enemy, loot, scene, warrior] = pools;
+    validate_resource_pools(action, card, system, enemy, loot, scene, warrior)
+}
+
+fn has(diagnostics: &[Diagnostic], kind: DiagnosticKind, pool: &str, id: Option<u64>) -> bool {
+    diagnostics
+        .iter()
+        .any(|v| v.kind == kind && v.pool == Some(pool) && v.id == id)
+}
+
+#[test]
+fn fixture_pools_are_clean() {
+    let diagnostics = validate(POOLS);
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn every_problem_is_reported() {
+    let card = with_entries(POOLS[1], json!([{ "id": 2, "action": 9 }]));
+    let enemy = with_entries(POOLS[3], json!([{ "id": 2, "name": "Rock" }]));
+    let scene = with_entries(
+        POOLS[5],
+        json!([{ "id": 2, "nodes": [{ "point": { "x": 0, "y": 0 }, "enemies": [1] }] }]),
+    );
+    let warrior = with_entries(
+        POOLS[6],
+        json!([
+            { "id": 2, "charactor_card": 9, "deck": [1] },
+            { "id": 3, "charactor_card": 1, "deck": [1, 9] },
+        ]),
+    );
+    let mut pools = POOLS;
+    pools[1] = &card;
+    pools[2] = "{ \"systems\": [";
+    pools[3] = &enemy;
+    pools[5] = &scene;
+    pools[6] = &warrior;
+    let diagnostics = validate(pools);
+    assert!(has(
+        &diagnostics,
+        DiagnosticKind::DuplicateId,
+        "card",
+        Some(2)
+    ));
+    assert!(has(
+        &diagnostics,
+        DiagnosticKind::DanglingReference,
+        "card",
+        Some(2)
+    ));
+    // the character card and the deck both hold card ids
+    for id in [2, 3] {
+        assert!(has(
+            &diagnostics,
+            DiagnosticKind::DanglingReference,
+            "warrior",
+            Some(id)
+        ));
+    }
+    assert!(has(
+        &diagnostics,
+        DiagnosticKind::Unparsable,
+        "system",
+        None
+    ));
+    assert!(has(
+        &diagnostics,
+        DiagnosticKind::EnemyWithoutActions,
+        "enemy",
+        Some(2)
+    ));
+    assert!(has(
+        &diagnostics,
+        DiagnosticKind::NoStartingArea,
+        "scene",
+        Some(2)
+    ));
+    // reported by this crate, so core never got to see the pools
+    assert!(diagnostics
+        .iter()
+        .all(|v| v.kind != DiagnosticKind::Rejected));
+}
+
+#[test]
+fn inspect_fixture_binary() {
+    let inspection = inspect_resource_binary(&fixture_pool()).unwrap();
+    let counts: Vec<_> = inspection.pools.iter().map(|v| (v.pool, v.count)).collect();
+    assert_eq!(
+        counts,
+        [
+            ("action", 3),
+            ("card", 2),
+            ("system", 1),
+            ("enemy", 1),
+            ("loot", 1),
+            ("scene", 1),
+            ("warrior", 1)
+        ]
+    );
+    let warriors = inspection
+        .pools
+        .iter()
+        .find(|v| v.pool == "warrior")
+        .unwrap();
+    assert_eq!(warriors.ids, [PLAYER_ID as u64]);
+    assert!(matches!(
+        inspect_resource_binary(b"not a resource binary"),
+        Err(GameError::InvalidPayload { .. })
+    ));
+}
+
+#[test]
+fn diff_lists_added_entries() {
+    let pool = fixture_pool();
+    assert!(diff_resource_binaries(&pool, &pool).unwrap().is_empty());
+
+    let card = with_entries(
+        POOLS[1],
+        json!([{ "id": 3, "name": "Bash", "class": "Attack", "cost": 2, "action": 1 }]),
+    );
+    let [action, _, system, enemy, loot, scene, warrior] = POOLS;
+    let changed =
+        generate_resource_binary(action, &card, system, enemy, loot, scene, warrior).unwrap();
+    let diffs = diff_resource_binaries(&pool, &changed).unwrap();
+    assert_eq!(diffs.len(), 1, "{diffs:?}");
+    assert_eq!(
+        (diffs[0].pool, diffs[0].change),
+        ("card", EntryChange::Added)
+    );
+    assert_eq!(diffs[0].id, 3);
+    assert!(diffs[0].before.is_none() && diffs[0].after.is_some());
+}
+
+#[test]
+fn resource_header_is_checked() {
+    let pool = fixture_pool();
+    let (header, content) = unpack_resource_binary(&pool).unwrap();
+    assert_eq!(header.version, 1);
+    // a rebuild of the same content only differs in its timestamp
+    let rebuilt = pack_resource_binary(content, header.built_at + 1);
+    assert_eq!(
+        unpack_resource_binary(&rebuilt).unwrap().0.hash,
+        header.hash
+    );
+
+    let mut newer = pool.clone();
+    newer[4] = 2;
+    assert!(matches!(
+        Context::create(&newer, SEED),
+        Err(GameError::UnsupportedVersion {
+            found: 2,
+            expected: 1,
+            ..
+        })
+    ));
+    let mut corrupted = pool.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    assert!(matches!(
+        Context::create(&corrupted, SEED),
+        Err(GameError::InvalidPayload { .. })
+    ));
+    assert!(matches!(
+        Context::create(content, SEED),
+        Err(GameError::InvalidPayload { .. })
+    ));
+}
+
+#[test]
+fn snapshots_survive_a_rebuild_of_the_same_pools() {
+    let pool = fixture_pool();
+    let (header, content) = unpack_resource_binary(&pool).unwrap();
+    let rebuilt = pack_resource_binary(content, header.built_at + 60);
+    let context = Context::create(&pool, SEED).unwrap();
+    context
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+    let snapshot = context.export_snapshot().unwrap();
+    let restored = Context::create(&rebuilt, SEED).unwrap();
+    restored.import_snapshot(&snapshot).unwrap();
+}
+
+#[test]
+fn malformed_potions_are_explained() {
+    let pool = fixture_pool();
+    for potion in [&[][..], &[0xff; 3]] {
+        match inspect_potion(&pool, potion) {
+            Err(GameError::InvalidSelection { subject, reason }) => {
+                assert_eq!(subject, "potion");
+                assert!(!reason.is_empty());
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,645 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{fixture_pool, END_TURN, FIGHT, FIRST_ENEMY, PLAYER_ID, SEED, START, UNKNOWN_CARD};
+use serde_json::json;
+use spore_warriors_wasm::autopilot::RunOutcome;
+use spore_warriors_wasm::daily::run_digest;
+use spore_warriors_wasm::events::current_turn;
+use spore_warriors_wasm::health::SubsystemHealth;
+use spore_warriors_wasm::navigation::reachable_points;
+use spore_warriors_wasm::policy::{outcome, played_card};
+use spore_warriors_wasm::replay::{Replay, ReplayStep};
+use spore_warriors_wasm::resources::pack_resource_binary;
+use spore_warriors_wasm::snapshot::{Action, Snapshot};
+use spore_warriors_wasm::visibility::{visibility, visible_profile};
+use spore_warriors_wasm::{
+    autopilot, create_daily_game, list_warriors, simulate_battles, smoke_test_run,
+    verify_daily_run, verify_replay, Context, GameError, IterationInput, JsonRenderer, Policy,
+    SeedInfo,
+};
+
+fn session() -> Arc<Context> {
+    let context = Context::create(&fixture_pool(), SEED).unwrap();
+    context
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+    context
+}
+
+fn fight(context: &Context) {
+    context
+        .move_player(FIGHT.0, FIGHT.1, vec![], &JsonRenderer)
+        .unwrap();
+    assert!(context.has_battle().unwrap());
+}
+
+fn end_turn() -> Vec<IterationInput> {
+    serde_json::from_str(END_TURN).unwrap()
+}
+
+#[test]
+fn full_run() {
+    let context = session();
+    context.map_profile(&JsonRenderer).unwrap();
+    context.warrior_profile(&JsonRenderer).unwrap();
+    context.deck_profile(&JsonRenderer).unwrap();
+    assert!(context
+        .peak_movement(FIGHT.0, FIGHT.1, &JsonRenderer)
+        .unwrap()
+        .is_some());
+
+    fight(&context);
+    context.start_battle(&JsonRenderer).unwrap();
+    context.iterate_battle(end_turn(), &JsonRenderer).unwrap();
+    context.destroy_battle().unwrap();
+    assert!(!context.has_battle().unwrap());
+    context.warrior_profile(&JsonRenderer).unwrap();
+
+    context.end_session().unwrap();
+    context.release().unwrap();
+}
+
+#[test]
+fn battle_events() {
+    let context = session();
+    fight(&context);
+
+    let mut events = vec![];
+    let step = context
+        .start_battle_with(&JsonRenderer, |event| events.push(event))
+        .unwrap();
+    assert_eq!(events.len(), step[1].as_array().unwrap().len());
+    assert!(events.iter().all(|event| event.turn == 0));
+
+    events.clear();
+    let step = context
+        .iterate_battle_with(end_turn(), &JsonRenderer, |event| events.push(event))
+        .unwrap();
+    assert_eq!(events.len(), step[1].as_array().unwrap().len());
+    for (sequence, event) in events.iter().enumerate() {
+        assert_eq!(event.turn, 1);
+        assert_eq!(event.sequence, sequence);
+        assert_eq!(event.log, step[1][sequence]);
+        // every log is a variant of core's log enum
+        assert!(!event.kind.is_empty());
+    }
+}
+
+#[test]
+fn only_ended_turns_advance_the_turn() {
+    let iterate = |operations: &str| Action::IterateBattle {
+        operations: serde_json::from_str(operations).unwrap(),
+    };
+    let card = r#"[{ "HandCardUse": [0, null] }]"#;
+    let mut actions = vec![Action::StartBattle, iterate(card)];
+    assert_eq!(current_turn(&actions), 0);
+    actions.push(iterate(END_TURN));
+    assert_eq!(current_turn(&actions), 1);
+    actions.extend([iterate(card), iterate(card)]);
+    assert_eq!(current_turn(&actions), 1);
+    // a new battle starts over
+    actions.push(Action::StartBattle);
+    assert_eq!(current_turn(&actions), 0);
+}
+
+#[test]
+fn same_seed_same_run() {
+    let first = session();
+    let second = session();
+    fight(&first);
+    fight(&second);
+    assert_eq!(
+        first.start_battle(&JsonRenderer).unwrap(),
+        second.start_battle(&JsonRenderer).unwrap()
+    );
+}
+
+#[test]
+fn invalid_resource_pool() {
+    assert!(matches!(
+        Context::create(b"not a resource pool", SEED),
+        Err(GameError::InvalidPayload {
+            payload: "resource binary",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn double_session() {
+    let context = session();
+    assert!(matches!(
+        context.create_session(PLAYER_ID, START.0, START.1, &[]),
+        Err(GameError::AlreadyInitialized { subject: "session" })
+    ));
+}
+
+#[test]
+fn no_session() {
+    let context = Context::create(&fixture_pool(), SEED).unwrap();
+    assert!(matches!(
+        context.warrior_profile(&JsonRenderer),
+        Err(GameError::NotInitialized { subject: "warrior" })
+    ));
+    assert!(matches!(
+        context.deck_profile(&JsonRenderer),
+        Err(GameError::NotInitialized { subject: "deck" })
+    ));
+    assert!(matches!(
+        context.move_player(FIGHT.0, FIGHT.1, vec![], &JsonRenderer),
+        Err(GameError::NotInitialized { subject: "warrior" })
+    ));
+}
+
+#[test]
+fn missing_battle() {
+    let context = session();
+    assert!(!context.has_battle().unwrap());
+    assert!(matches!(
+        context.start_battle(&JsonRenderer),
+        Err(GameError::BattleNotTriggered)
+    ));
+    assert!(matches!(
+        context.iterate_battle(end_turn(), &JsonRenderer),
+        Err(GameError::BattleNotTriggered)
+    ));
+    assert!(matches!(
+        context.peak_target(serde_json::from_str(FIRST_ENEMY).unwrap()),
+        Err(GameError::BattleNotTriggered)
+    ));
+    assert!(matches!(
+        context.destroy_battle(),
+        Err(GameError::BattleNotTriggered)
+    ));
+}
+
+#[test]
+fn move_while_battle_pending() {
+    let context = session();
+    fight(&context);
+    let before = context.export_snapshot().unwrap();
+    assert!(matches!(
+        context.move_player(START.0, START.1, vec![], &JsonRenderer),
+        Err(GameError::BattleAlreadyTriggered)
+    ));
+    assert_eq!(context.export_snapshot().unwrap(), before);
+}
+
+#[test]
+fn invalid_operations() {
+    let context = session();
+    fight(&context);
+    context.start_battle(&JsonRenderer).unwrap();
+    assert!(matches!(
+        context.iterate_battle(serde_json::from_str(UNKNOWN_CARD).unwrap(), &JsonRenderer),
+        Err(GameError::CoreError { .. })
+    ));
+}
+
+#[test]
+fn released_game() {
+    let context = session();
+    context.release().unwrap();
+    assert!(matches!(
+        context.map_profile(&JsonRenderer),
+        Err(GameError::NotInitialized { subject: "game" })
+    ));
+    assert!(matches!(
+        context.create_session(PLAYER_ID, START.0, START.1, &[]),
+        Err(GameError::NotInitialized { subject: "game" })
+    ));
+}
+
+#[test]
+fn snapshot_roundtrip() {
+    let context = session();
+    fight(&context);
+    let snapshot = context.export_snapshot().unwrap();
+
+    let restored = Context::create(&fixture_pool(), SEED + 1).unwrap();
+    restored.import_snapshot(&snapshot).unwrap();
+    assert!(restored.has_battle().unwrap());
+    assert_eq!(
+        context.warrior_profile(&JsonRenderer).unwrap(),
+        restored.warrior_profile(&JsonRenderer).unwrap()
+    );
+}
+
+#[test]
+fn snapshot_errors() {
+    let context = session();
+    assert!(matches!(
+        context.import_snapshot(b"junk"),
+        Err(GameError::InvalidPayload {
+            payload: "snapshot",
+            ..
+        })
+    ));
+
+    let mut snapshot = context.export_snapshot().unwrap();
+    snapshot[4] = 0xff;
+    assert!(matches!(
+        context.import_snapshot(&snapshot),
+        Err(GameError::UnsupportedVersion {
+            payload: "snapshot",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn replay_verifies() {
+    let context = session();
+    context.set_replay_recording(true).unwrap();
+    fight(&context);
+    context.start_battle(&JsonRenderer).unwrap();
+    let replay = context.export_replay().unwrap();
+
+    let report = verify_replay(&fixture_pool(), &replay).unwrap();
+    assert_eq!(report.executed_steps, report.total_steps);
+    assert!(report.divergence.is_none());
+
+    let another_pool = pack_resource_binary(b"another pool", 0);
+    assert!(matches!(
+        verify_replay(&another_pool, &replay),
+        Err(GameError::ResourcePoolMismatch { payload: "replay" })
+    ));
+}
+
+#[test]
+fn health_and_recover() {
+    let context = session();
+    let health = context.health();
+    assert!(health.consistent);
+    assert_eq!(health.warrior, SubsystemHealth::Ready);
+    assert_eq!(health.battle, SubsystemHealth::Empty);
+
+    fight(&context);
+    context.recover().unwrap();
+    assert!(context.has_battle().unwrap());
+    assert!(context.health().consistent);
+}
+
+#[test]
+fn concurrent_calls_wait_their_turn() {
+    let context = session();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..32 {
+                    context.warrior_profile(&JsonRenderer).unwrap();
+                    assert!(!context.has_battle().unwrap());
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn undo_snapshots_and_recover_interleave() {
+    let context = session();
+    context.set_history_capacity(1).unwrap();
+    fight(&context);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for _ in 0..8 {
+                context.undo().unwrap();
+                context.redo().unwrap();
+            }
+        });
+        scope.spawn(|| {
+            for _ in 0..8 {
+                context.recover().unwrap();
+            }
+        });
+        scope.spawn(|| {
+            for _ in 0..8 {
+                context.export_snapshot().unwrap();
+                context.warrior_profile(&JsonRenderer).unwrap();
+            }
+        });
+    });
+    assert!(context.has_battle().unwrap());
+}
+
+#[test]
+fn undo_and_redo() {
+    let context = session();
+    assert!(matches!(context.undo(), Err(GameError::NothingToUndo)));
+    context.set_history_capacity(2).unwrap();
+
+    let before = context.warrior_profile(&JsonRenderer).unwrap();
+    fight(&context);
+    context.start_battle(&JsonRenderer).unwrap();
+    context.undo().unwrap();
+    assert!(context.has_battle().unwrap());
+    context.undo().unwrap();
+    assert!(!context.has_battle().unwrap());
+    assert_eq!(context.warrior_profile(&JsonRenderer).unwrap(), before);
+    // the history only holds two steps
+    assert!(!context.can_undo().unwrap());
+
+    context.redo().unwrap();
+    assert!(context.has_battle().unwrap());
+    assert!(context.can_redo().unwrap());
+    context.redo().unwrap();
+    assert!(matches!(context.redo(), Err(GameError::NothingToRedo)));
+
+    // a fresh step drops whatever was left to redo
+    context.undo().unwrap();
+    context.start_battle(&JsonRenderer).unwrap();
+    assert!(!context.can_redo().unwrap());
+}
+
+#[test]
+fn derived_seeds() {
+    let pool = fixture_pool();
+    let plain = Context::create(&pool, SEED).unwrap().seed_info();
+    assert_eq!((plain.master, plain.map), (SEED, SEED));
+    assert_ne!(plain.battle, plain.map);
+
+    let context = Context::create_seeded(&pool, SEED, PLAYER_ID).unwrap();
+    let seeds = context.seed_info();
+    assert_eq!((seeds.master, seeds.player_id), (SEED, Some(PLAYER_ID)));
+    assert_ne!(seeds.map, SEED);
+    assert_eq!(seeds.battle, SeedInfo::battle_seed(seeds.map));
+    let again = Context::create_seeded(&pool, SEED, PLAYER_ID).unwrap();
+    assert_eq!(again.seed_info(), seeds);
+    assert_eq!(
+        context.map_profile(&JsonRenderer).unwrap(),
+        again.map_profile(&JsonRenderer).unwrap()
+    );
+    assert_ne!(
+        Context::create_seeded(&pool, SEED + 1, PLAYER_ID)
+            .unwrap()
+            .seed_info(),
+        seeds
+    );
+
+    assert!(matches!(
+        context.create_session(PLAYER_ID + 1, START.0, START.1, &[]),
+        Err(GameError::InvalidSelection {
+            subject: "player_id",
+            ..
+        })
+    ));
+    context
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+}
+
+#[test]
+fn daily_challenge() {
+    const DATE: &str = "2024-04-01";
+    let pool = fixture_pool();
+    assert!(matches!(
+        create_daily_game(&pool, "2023-02-29"),
+        Err(GameError::InvalidSelection {
+            subject: "date",
+            ..
+        })
+    ));
+
+    let context = create_daily_game(&pool, DATE).unwrap();
+    let challenge = context.daily().unwrap().clone();
+    assert_eq!(
+        create_daily_game(&pool, DATE).unwrap().daily(),
+        Some(&challenge)
+    );
+    let other = create_daily_game(&pool, "2024-04-02").unwrap();
+    assert_ne!(other.daily().unwrap().master_seed, challenge.master_seed);
+
+    // the session is already running and cannot be swapped for another one
+    assert!(context.is_ranked());
+    assert!(matches!(
+        context.create_session(challenge.player_id, 0, 0, &[]),
+        Err(GameError::AlreadyInitialized { subject: "session" })
+    ));
+    assert!(matches!(
+        context.end_session(),
+        Err(GameError::Ranked { .. })
+    ));
+    assert!(matches!(context.undo(), Err(GameError::Ranked { .. })));
+    // forks would see the draws ahead of the leaderboard run
+    assert!(matches!(
+        Policy::Lookahead.next_inputs(&context, 0),
+        Err(GameError::Ranked { .. })
+    ));
+    let before = context.export_snapshot().unwrap();
+    assert!(matches!(
+        autopilot(&context, Policy::Greedy, 4),
+        Err(GameError::Ranked { .. })
+    ));
+    assert_eq!(context.export_snapshot().unwrap(), before);
+
+    autopilot(&context, Policy::Random, 4).unwrap();
+    let digest = run_digest(&context).unwrap();
+    let replay = context.export_replay().unwrap();
+    let verified = verify_daily_run(&pool, DATE, &replay).unwrap();
+    assert_eq!(verified.digest, digest.digest);
+    assert_eq!(verified.steps, digest.steps);
+    assert!(matches!(
+        verify_daily_run(&pool, "2024-04-02", &replay),
+        Err(GameError::InvalidPayload { .. })
+    ));
+
+    // a run that swaps its warrior for a standalone one is refused before it is played
+    let mut forged = Replay::decode(&replay).unwrap();
+    forged.steps.truncate(1);
+    let mut warrior = context.warrior_profile(&JsonRenderer).unwrap();
+    warrior["hp"] = json!(9999);
+    forged.steps.extend(
+        [
+            Action::StandaloneBattle {
+                warrior,
+                deck: context.deck_profile(&JsonRenderer).unwrap(),
+                enemies: json!([]),
+            },
+            Action::DestroyBattle,
+        ]
+        .map(|action| ReplayStep {
+            action,
+            digest: None,
+        }),
+    );
+    match verify_daily_run(&pool, DATE, &forged.encode().unwrap()) {
+        Err(GameError::InvalidPayload { payload, reason }) => {
+            assert_eq!(payload, "daily run");
+            assert!(reason.starts_with("step 1 "), "{reason}");
+        }
+        other => panic!("forged run accepted: {other:?}"),
+    }
+    assert!(matches!(
+        run_digest(&session()),
+        Err(GameError::NotInitialized { .. })
+    ));
+}
+
+#[test]
+fn warrior_catalog() {
+    let context = Context::create(&fixture_pool(), SEED).unwrap();
+    let warriors = list_warriors(&context).unwrap();
+    assert_eq!(warriors.len(), 1);
+    let warrior = &warriors[0];
+    assert_eq!(warrior.player_id, PLAYER_ID);
+    assert!(warrior.starting_points.contains(&START));
+    assert!(warrior.stats.contains_key("hp"));
+    // listing only plays throwaway sessions
+    assert!(matches!(
+        context.warrior_profile(&JsonRenderer),
+        Err(GameError::NotInitialized { .. })
+    ));
+    context
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+}
+
+#[test]
+fn undo_disabled_when_ranked() {
+    let context = Context::create_ranked(&fixture_pool(), SEED).unwrap();
+    assert!(context.is_ranked());
+    assert!(matches!(
+        context.set_history_capacity(8),
+        Err(GameError::Ranked {
+            operation: "history"
+        })
+    ));
+    assert!(matches!(
+        context.undo(),
+        Err(GameError::Ranked { operation: "undo" })
+    ));
+    let snapshot = session().export_snapshot().unwrap();
+    assert!(matches!(
+        context.import_snapshot(&snapshot),
+        Err(GameError::Ranked {
+            operation: "importing a snapshot"
+        })
+    ));
+}
+
+#[test]
+fn standalone_battle_only_opens_a_fresh_game() {
+    let context = session();
+    let mut warrior = context.warrior_profile(&JsonRenderer).unwrap();
+    warrior["hp"] = json!(9999);
+    let mut snapshot = Snapshot::decode(&context.export_snapshot().unwrap()).unwrap();
+    snapshot.actions.extend([
+        Action::StandaloneBattle {
+            warrior: warrior.clone(),
+            deck: context.deck_profile(&JsonRenderer).unwrap(),
+            enemies: json!([]),
+        },
+        Action::DestroyBattle,
+    ]);
+    snapshot.warrior = Some(warrior);
+    assert!(matches!(
+        context.import_snapshot(&snapshot.encode().unwrap()),
+        Err(GameError::InvalidSelection {
+            subject: "standalone battle",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn simulate_inputs() {
+    let context = session();
+    let warrior = context.warrior_profile(&JsonRenderer).unwrap();
+    let deck = context.deck_profile(&JsonRenderer).unwrap();
+    let pool = fixture_pool();
+
+    let report =
+        simulate_battles(&pool, &warrior, &deck, &json!([]), Policy::Greedy, 0, SEED).unwrap();
+    assert_eq!(report.runs, 0);
+    assert_eq!(report.win_rate, 0.0);
+
+    assert!(matches!(
+        simulate_battles(&pool, &warrior, &deck, &json!(42), Policy::Passive, 1, SEED),
+        Err(GameError::Deserialize {
+            subject: "enemies",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn policies_play_turns() {
+    for policy in [
+        Policy::Passive,
+        Policy::Random,
+        Policy::Greedy,
+        Policy::Lookahead,
+    ] {
+        let context = session();
+        fight(&context);
+        context.start_battle(&JsonRenderer).unwrap();
+
+        let before = context.export_snapshot().unwrap();
+        let suggested = policy.next_inputs(&context, 0).unwrap();
+        assert!(suggested.is_array());
+        // suggestions are tried on forks, the battle itself is untouched
+        assert_eq!(context.export_snapshot().unwrap(), before);
+
+        let mut events = 0;
+        let step = policy.play_turn(&context, |_| events += 1).unwrap();
+        assert!(events >= step[1].as_array().unwrap().len());
+        // steps read back into core's own output and log enums
+        outcome(&step[0]).unwrap();
+        for log in step[1].as_array().unwrap() {
+            played_card(log).unwrap();
+        }
+    }
+}
+
+#[test]
+fn autopilot_smoke_test() {
+    let summary = smoke_test_run(&fixture_pool(), SEED, PLAYER_ID, START, Policy::Greedy).unwrap();
+    assert_eq!(summary.path.first(), Some(&FIGHT));
+    assert_eq!(summary.battles_won + summary.battles_lost, 1);
+    assert_ne!(summary.outcome, RunOutcome::StepLimit);
+}
+
+#[test]
+fn reachable_points_preview_nodes() {
+    let context = session();
+    let points = reachable_points(&context).unwrap();
+    assert!(points.iter().any(|v| v.point == FIGHT));
+    for point in &points {
+        assert_eq!(
+            Some(&point.node),
+            context
+                .peak_movement(point.point.0, point.point.1, &JsonRenderer)
+                .unwrap()
+                .as_ref()
+        );
+    }
+    // previewing never moves the warrior
+    assert!(!context.has_battle().unwrap());
+}
+
+#[test]
+fn fog_of_war() {
+    let context = session();
+    let seen = visibility(&context).unwrap();
+    assert_eq!(seen.visited, [START].into());
+    assert!(seen.revealed.contains(&FIGHT));
+
+    // the far corner of the fixture scene is neither visited nor one move away
+    let profile = visible_profile(&context).unwrap();
+    let points: Vec<_> = profile.nodes.iter().map(|v| v.point).collect();
+    assert!(points.contains(&START) && points.contains(&FIGHT));
+    assert!(!points.contains(&(2, 2)));
+
+    fight(&context);
+    let moved = visibility(&context).unwrap();
+    assert!(moved.visited.contains(&FIGHT));
+    // what was seen from the start stays seen after moving on, and after an undo rebuild
+    assert!(moved.revealed.is_superset(&seen.revealed));
+    context.undo().unwrap();
+    context.redo().unwrap();
+    assert_eq!(visibility(&context).unwrap().revealed, moved.revealed);
+    context.end_session().unwrap();
+    assert!(visibility(&context).unwrap().visited.is_empty());
+}
@@ -0,0 +1,111 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+mod common;
+
+use common::{fixture_pool, END_TURN, FIGHT, PLAYER_ID, SEED, START};
+use spore_warriors_wasm::wasm::{create_game, create_standalone_battle, WasmGame};
+use spore_warriors_wasm::GameError;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn session() -> WasmGame {
+    let game = create_game(&fixture_pool(), SEED).unwrap();
+    game.create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap();
+    game
+}
+
+fn end_turn<T: JsCast>() -> T {
+    js_sys::JSON::parse(END_TURN).unwrap().unchecked_into()
+}
+
+fn kind(error: GameError) -> JsValue {
+    js_sys::Reflect::get(&error.into(), &"kind".into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn full_run() {
+    let game = session();
+    game.get_potion().unwrap();
+    let map = game.get_map();
+    map.get_visible_profile().unwrap();
+    map.get_warrior_profile().unwrap();
+    map.get_warrior_deck_profile().unwrap();
+    assert!(!JsValue::from(map.peak_movement(FIGHT.0, FIGHT.1).unwrap()).is_null());
+
+    map.move_player(FIGHT.0, FIGHT.1, vec![]).unwrap();
+    let battle = map.create_pve_battle().unwrap();
+    let step: JsValue = battle.start().unwrap().into();
+    assert!(js_sys::Array::is_array(&step));
+    battle.iterate(end_turn()).unwrap();
+    battle.destroy().unwrap();
+
+    game.end_session().unwrap();
+    game.destroy().unwrap();
+}
+
+#[wasm_bindgen_test]
+fn standalone_battle_with_invalid_enemies() {
+    let game = session();
+    let map = game.get_map();
+    let error = create_standalone_battle(
+        &game,
+        map.get_warrior_profile().unwrap(),
+        map.get_warrior_deck_profile().unwrap(),
+        JsValue::from(42).unchecked_into(),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(
+        error,
+        GameError::Deserialize {
+            subject: "enemies",
+            ..
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+fn double_session() {
+    let game = session();
+    let error = game
+        .create_session(PLAYER_ID, START.0, START.1, &[])
+        .unwrap_err();
+    assert_eq!(kind(error), "AlreadyInitialized");
+}
+
+#[wasm_bindgen_test]
+fn missing_battle() {
+    let game = session();
+    let error = game.get_map().create_pve_battle().err().unwrap();
+    assert_eq!(kind(error), "BattleNotTriggered");
+}
+
+#[wasm_bindgen_test]
+fn invalid_operations() {
+    let game = session();
+    let map = game.get_map();
+    map.move_player(FIGHT.0, FIGHT.1, vec![]).unwrap();
+    let battle = map.create_pve_battle().unwrap();
+    battle.start().unwrap();
+    let error = battle
+        .iterate(JsValue::from(42).unchecked_into())
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        GameError::InvalidSelection {
+            subject: "operations",
+            ..
+        }
+    ));
+}
+
+#[wasm_bindgen_test]
+fn structured_error() {
+    let game = create_game(&fixture_pool(), SEED).unwrap();
+    let error: JsValue = game.get_map().get_warrior_profile().unwrap_err().into();
+    let field = |name: &str| js_sys::Reflect::get(&error, &name.into()).unwrap();
+    assert_eq!(field("kind"), "NotInitialized");
+    assert_eq!(field("subject"), "warrior");
+    assert_eq!(field("message"), "warrior is not initialized");
+}